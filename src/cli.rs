@@ -0,0 +1,859 @@
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+
+use crate::{gift_shop, lobby, printing_department};
+
+const USAGE: &str = "\
+usage: aoc-2025 [<command>]
+
+Without a command every day is solved against its bundled input.
+
+Gift Shop range lists take one entry per line or comma, optionally labelled
+(`warehouse-a: 100-200`), as `start-end`, open-ended `start-` (up to the last
+ID with as many digits) or a single ID; `#` starts a comment.
+
+Lobby banks take one bank per line, either one digit per battery or joltages
+of any size separated by commas (`12,7,15,3`); chosen joltages are written
+one after another.
+
+commands:
+  lobby [--input <file>] [--select <k,...>] [--show]
+      maximum joltage per bank and in total for each selection size
+      (default: 2,12); `--show` marks the chosen batteries of every bank
+      constraints: [--min-gap <n>] [--exclude <i,...>] [--max-run <n>] keep
+      `n` unused cells between chosen ones, skip 0-based positions, and cap
+      how many chosen cells may sit next to each other
+      [--objective <largest|smallest|digit-sum|rank:n>] picks the smallest
+      number without a leading 0, the largest sum of the digits spelled, or
+      the n-th largest distinct number instead; not combinable with
+      constraints
+  lobby --cells <l-r,...> [--input <file>] [--select <k,...>] [--show]
+      maximum joltage per bank using only the 0-based cells `l..=r`, for
+      each range and selection size; a bank too short for a range shows `-`
+  lobby --edit <op,...> [--input <file>] [--select <k,...>]
+      apply `set:<i>:<joltage>`, `insert:<i>:<joltage>` and `remove:<i>`
+      (0-based positions) to every bank in turn, printing the joltage each
+      edit replaced or removed, the cell count and the maximum joltage per
+      selection size before and after each edit
+  lobby --profile [--input <file>]
+      maximum joltage of every bank for every selection size, as
+      `bank<TAB>k<TAB>joltage`; takes no constraints or objective
+  lobby --stream [<file>] [--select <k,...>]
+      read one huge digit-per-battery bank (from stdin when no file is given)
+      in a single pass with memory bounded by the selection size; whitespace
+      is ignored
+  lobby --global <k> [--input <file>] [--min-per-bank <n>] [--max-per-bank <n>]
+      choose `k` batteries across all banks for the largest total joltage,
+      taking between min and max from each bank; prints
+      `bank<TAB>count<TAB>joltage<TAB>indices` per bank and the total; takes
+      no constraints or objective
+  printing-department [--input <file>]
+      rolls of paper a forklift can reach at first and after removing every
+      reachable roll in turn; the floor is sized from the input
+      [--neighborhood <moore|von-neumann|moore:r|offsets:dx,dy;...>]
+      [--threshold <n>] a roll is reachable with fewer than `n` (default 4)
+      rolls among its neighbors
+      [--boundary <empty|walls|torus>] past the edge of the floor is empty
+      space (default), wall that counts as a roll, or the opposite edge
+      [--waves] remove every reachable roll at once, round by round, and print
+      `round<TAB>removed<TAB>x,y ...` per round
+      [--rounds] print `x<TAB>y<TAB>round` for every roll, `-` if it stays
+      [--packed] run on a bit-packed copy of the floor, 64 cells per word, for
+      very large floors; default neighborhood, threshold and boundary only,
+      and no waves, rounds or export
+      [--export <dir>] [--scale <n>] write initial, per-wave and final PPM
+      and PGM images plus `animation.ppm`, every frame in one PPM stream;
+      green rolls go in the first wave, blue ones later and red ones stay
+  gift-shop sums [--input <file>] [--merge] [--parallel]
+      identical-halves and repeating-pattern sums; `--merge` counts IDs
+      covered by several ranges once, `--parallel` uses every core
+  gift-shop overlaps [--input <file>]
+      list pairs of input ranges that share IDs
+  gift-shop ids [--input <file>] [--output <file>] [--merge]
+      list every repeating-pattern product ID as `id<TAB>seed<TAB>repetitions`
+  gift-shop report [--input <file>]
+      per-range subtotals, a breakdown by digits, repetitions and period,
+      and totals per digit length (each ID once) and per repetition count
+  gift-shop check [<file>] [--output <file>]
+      read one ID per line (from stdin when no file is given) and print
+      `id<TAB>valid` or `id<TAB>invalid<TAB>seed<TAB>repetitions<TAB>period`
+  gift-shop around <id>
+      nearest repeating-pattern IDs at or below/above the ID and the next
+      valid ID after it
+  gift-shop flag [--input <file>] [--output <file>] [--rules <rule,...>] [--template <pattern>]...
+      list IDs matched by any of the rules as `id<TAB>rule,...`; rules are
+      identical-halves, repeating-pattern, palindrome and uniform-digits
+      (default: identical-halves,repeating-pattern). Templates use digits,
+      `?` for any digit and letters for digits that must repeat, e.g. `ab?ba`";
+
+pub fn run(args: &[String]) -> Result<(), String> {
+    let mut args = args.to_vec();
+    let command = args.remove(0);
+
+    match command.as_str() {
+        "gift-shop" => gift_shop_command(args),
+        "lobby" => lobby_command(args),
+        "printing-department" => printing_department_command(args),
+        "help" | "--help" | "-h" => {
+            println!("{USAGE}");
+            Ok(())
+        }
+        _ => Err(format!("unknown command `{command}`\n\n{USAGE}")),
+    }
+}
+
+fn gift_shop_command(mut args: Vec<String>) -> Result<(), String> {
+    if args.is_empty() {
+        return Err(format!("missing gift-shop subcommand\n\n{USAGE}"));
+    }
+
+    let subcommand = args.remove(0);
+    match subcommand.as_str() {
+        "sums" => {
+            let ranges = read_ranges(take_option(&mut args, "--input")?)?;
+            let options = gift_shop::ScanOptions {
+                merge_overlaps: take_flag(&mut args, "--merge"),
+                parallel: take_flag(&mut args, "--parallel"),
+            };
+            ensure_consumed(&args)?;
+
+            let sums = gift_shop::scan(&ranges, options);
+            println!("identical halves sum:  {}", sums.identical_halves_sum);
+            println!("repeating pattern sum: {}", sums.repeating_patterns_sum);
+            Ok(())
+        }
+        "overlaps" => {
+            let ranges = read_ranges(take_option(&mut args, "--input")?)?;
+            ensure_consumed(&args)?;
+
+            let bounds: Vec<_> = ranges.iter().map(gift_shop::ProductRange::bounds).collect();
+            for overlap in gift_shop::find_overlaps(&bounds) {
+                println!(
+                    "#{} ({}) and #{} ({}) share {}-{}",
+                    overlap.first + 1,
+                    ranges[overlap.first],
+                    overlap.second + 1,
+                    ranges[overlap.second],
+                    overlap.start,
+                    overlap.end
+                );
+            }
+
+            Ok(())
+        }
+        "ids" => {
+            let ranges = read_ranges(take_option(&mut args, "--input")?)?;
+            let mut out = open_output(take_option(&mut args, "--output")?)?;
+            let options = gift_shop::ScanOptions {
+                merge_overlaps: take_flag(&mut args, "--merge"),
+                ..gift_shop::ScanOptions::default()
+            };
+            ensure_consumed(&args)?;
+
+            for (start, end) in gift_shop::scan_bounds(&ranges, options) {
+                for pattern in gift_shop::repeating_patterns(start, end) {
+                    writeln!(
+                        out,
+                        "{}\t{}\t{}",
+                        pattern.id, pattern.seed, pattern.repetitions
+                    )
+                    .map_err(|e| e.to_string())?;
+                }
+            }
+
+            out.flush().map_err(|e| e.to_string())
+        }
+        "report" => {
+            let ranges = read_ranges(take_option(&mut args, "--input")?)?;
+            ensure_consumed(&args)?;
+
+            print_gift_shop_report(&gift_shop::build_report(&ranges));
+            Ok(())
+        }
+        "check" => {
+            let mut out = open_output(take_option(&mut args, "--output")?)?;
+            let reader = open_reader(args.pop_if(|arg| !arg.starts_with("--")))?;
+            ensure_consumed(&args)?;
+
+            for (number, line) in reader.lines().enumerate() {
+                let line = line.map_err(|e| e.to_string())?;
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+
+                let id: usize = line
+                    .parse()
+                    .map_err(|_| format!("line {}: invalid product ID `{line}`", number + 1))?;
+
+                match gift_shop::is_repeating_pattern(id) {
+                    Some(pattern) => writeln!(
+                        out,
+                        "{id}\tinvalid\t{}\t{}\t{}",
+                        pattern.seed,
+                        pattern.repetitions,
+                        pattern.period()
+                    ),
+                    None => writeln!(out, "{id}\tvalid"),
+                }
+                .map_err(|e| e.to_string())?;
+            }
+
+            out.flush().map_err(|e| e.to_string())
+        }
+        "around" => {
+            let id = args.pop_if(|arg| !arg.starts_with("--"));
+            ensure_consumed(&args)?;
+
+            let id = id.ok_or_else(|| format!("missing product ID\n\n{USAGE}"))?;
+            let id: usize = id
+                .parse()
+                .map_err(|_| format!("invalid product ID `{id}`"))?;
+
+            let describe = |pattern: Option<gift_shop::RepeatingPattern>| match pattern {
+                Some(p) => format!("{} ({} x {})", p.id, p.seed, p.repetitions),
+                None => "none".to_owned(),
+            };
+
+            println!(
+                "previous pattern: {}",
+                describe(gift_shop::previous_repeating_pattern(id))
+            );
+            println!(
+                "next pattern:     {}",
+                describe(gift_shop::next_repeating_pattern(id))
+            );
+            match gift_shop::next_valid_id(id) {
+                Some(valid) => println!("next valid ID:    {valid}"),
+                None => println!("next valid ID:    none"),
+            }
+
+            Ok(())
+        }
+        "flag" => {
+            let ranges = read_ranges(take_option(&mut args, "--input")?)?;
+            let mut out = open_output(take_option(&mut args, "--output")?)?;
+
+            let mut rules: Vec<Box<dyn gift_shop::IdRule>> = Vec::new();
+            let names = take_option(&mut args, "--rules")?;
+            for name in names
+                .as_deref()
+                .unwrap_or("identical-halves,repeating-pattern")
+                .split(',')
+            {
+                rules.push(match name.trim() {
+                    "identical-halves" => Box::new(gift_shop::IdenticalHalves),
+                    "repeating-pattern" => Box::new(gift_shop::RepeatedChunk),
+                    "palindrome" => Box::new(gift_shop::Palindrome),
+                    "uniform-digits" => Box::new(gift_shop::UniformDigits),
+                    other => return Err(format!("unknown rule `{other}`")),
+                });
+            }
+
+            while let Some(template) = take_option(&mut args, "--template")? {
+                let template: gift_shop::DigitTemplate = template
+                    .parse()
+                    .map_err(|e| format!("template `{template}`: {e}"))?;
+                rules.push(Box::new(template));
+            }
+            ensure_consumed(&args)?;
+
+            let rule_set = gift_shop::RuleSet::new(rules);
+            for range in &ranges {
+                for found in rule_set.matches(range.start, range.end) {
+                    let names: Vec<&str> = found
+                        .rules
+                        .iter()
+                        .map(|&i| rule_set.rules()[i].name())
+                        .collect();
+
+                    writeln!(out, "{}\t{}", found.id, names.join(","))
+                        .map_err(|e| e.to_string())?;
+                }
+            }
+
+            out.flush().map_err(|e| e.to_string())
+        }
+        _ => Err(format!(
+            "unknown gift-shop subcommand `{subcommand}`\n\n{USAGE}"
+        )),
+    }
+}
+
+fn lobby_command(mut args: Vec<String>) -> Result<(), String> {
+    if take_flag(&mut args, "--stream") {
+        return lobby_stream(args);
+    }
+
+    let input = read_input(take_option(&mut args, "--input")?, lobby::INPUT_TEXT)?;
+    let banks = lobby::BatteryBank::parse_all(&input).map_err(|e| e.to_string())?;
+    let sizes = parse_sizes(
+        take_option(&mut args, "--select")?
+            .as_deref()
+            .unwrap_or("2,12"),
+    )?;
+    let show = take_flag(&mut args, "--show");
+    let profile = take_flag(&mut args, "--profile");
+    let global = take_option(&mut args, "--global")?;
+    let edits = match take_option(&mut args, "--edit")? {
+        Some(list) => Some(
+            list.split(',')
+                .map(|edit| {
+                    edit.parse::<lobby::BankEdit>()
+                        .map_err(|e| format!("{e}: `{edit}`"))
+                })
+                .collect::<Result<Vec<_>, _>>()?,
+        ),
+        None => None,
+    };
+    let cells = match take_option(&mut args, "--cells")? {
+        Some(list) => Some(parse_cells(&list)?),
+        None => None,
+    };
+    let quota = lobby::BankQuota {
+        min: parse_count(take_option(&mut args, "--min-per-bank")?, 0)?,
+        max: parse_count(take_option(&mut args, "--max-per-bank")?, usize::MAX)?,
+    };
+    let constraints = lobby::SelectionConstraints {
+        min_gap: parse_count(take_option(&mut args, "--min-gap")?, 0)?,
+        excluded: match take_option(&mut args, "--exclude")? {
+            Some(list) => parse_sizes(&list)?.into_iter().collect(),
+            None => Default::default(),
+        },
+        max_run: match take_option(&mut args, "--max-run")? {
+            Some(max) => Some(parse_count(Some(max), 0)?),
+            None => None,
+        },
+    };
+    let objective: lobby::Objective = match take_option(&mut args, "--objective")? {
+        Some(objective) => objective
+            .parse()
+            .map_err(|e| format!("{e}: `{objective}`"))?,
+        None => lobby::Objective::default(),
+    };
+    ensure_consumed(&args)?;
+
+    let constrained = constraints != lobby::SelectionConstraints::default();
+    if constrained && objective != lobby::Objective::default() {
+        return Err("--objective cannot be combined with constraints".to_owned());
+    }
+
+    let custom = constrained || objective != lobby::Objective::default();
+    if edits.is_some() && (custom || profile || show || global.is_some() || cells.is_some()) {
+        return Err("--edit only combines with --select".to_owned());
+    }
+
+    if cells.is_some() && (custom || profile || global.is_some()) {
+        return Err("--cells only combines with --select and --show".to_owned());
+    }
+
+    if custom && (profile || global.is_some()) {
+        return Err(
+            "--profile and --global cannot be combined with constraints or --objective".to_owned(),
+        );
+    }
+    let select = |bank: &lobby::BatteryBank, k: usize| {
+        if constrained {
+            bank.select_constrained(k, &constraints)
+        } else {
+            bank.select_by(k, objective)
+        }
+    };
+
+    if let Some(k) = global {
+        let k = parse_count(Some(k), 0)?;
+        let selection = lobby::select_across(&banks, k, &vec![quota; banks.len()])
+            .ok_or_else(|| format!("no way to choose {k} batteries within the quotas"))?;
+
+        println!("bank\tcount\tjoltage\tindices");
+        for (i, bank) in selection.banks.iter().enumerate() {
+            let indices: Vec<String> = bank.indices.iter().map(ToString::to_string).collect();
+            println!(
+                "{}\t{}\t{}\t{}",
+                i + 1,
+                bank.indices.len(),
+                bank.joltage,
+                indices.join(",")
+            );
+        }
+        println!("total\t{k}\t{}", selection.total);
+
+        return Ok(());
+    }
+
+    if profile {
+        let mut out = open_output(None)?;
+        writeln!(out, "bank\tk\tjoltage").map_err(|e| e.to_string())?;
+
+        for (i, bank) in banks.iter().enumerate() {
+            for (k, joltage) in bank.power_profile().iter() {
+                writeln!(out, "{}\t{k}\t{joltage}", i + 1).map_err(|e| e.to_string())?;
+            }
+        }
+
+        return out.flush().map_err(|e| e.to_string());
+    }
+
+    if let Some(edits) = edits {
+        return lobby_edits(&banks, &edits, &sizes);
+    }
+
+    if let Some(cells) = cells {
+        return lobby_cells(&banks, &cells, &sizes, show);
+    }
+
+    if show {
+        for (i, bank) in banks.iter().enumerate() {
+            for &k in &sizes {
+                match select(bank, k) {
+                    Some(selection) => {
+                        println!("bank {} k={k}: {}", i + 1, selection.joltage);
+                        println!("{}\n", bank.render_selection(&selection));
+                    }
+                    None => println!("bank {} k={k}: no valid selection\n", i + 1),
+                }
+            }
+        }
+
+        return Ok(());
+    }
+
+    if custom {
+        let header: Vec<String> = sizes.iter().map(|k| format!("k={k}")).collect();
+        println!("bank\t{}", header.join("\t"));
+
+        let mut totals = vec![lobby::Joltage::default(); sizes.len()];
+        for (i, bank) in banks.iter().enumerate() {
+            let mut row = Vec::with_capacity(sizes.len());
+            for (total, &k) in totals.iter_mut().zip(&sizes) {
+                match select(bank, k) {
+                    Some(selection) => {
+                        *total += &selection.joltage;
+                        row.push(selection.joltage.to_string());
+                    }
+                    None => row.push("-".to_owned()),
+                }
+            }
+
+            println!("{}\t{}", i + 1, row.join("\t"));
+        }
+
+        let totals: Vec<String> = totals.iter().map(ToString::to_string).collect();
+        println!("total\t{}", totals.join("\t"));
+
+        return Ok(());
+    }
+
+    let report = lobby::run_selection(&banks, &sizes);
+    let header: Vec<String> = sizes.iter().map(|k| format!("k={k}")).collect();
+    println!("bank\t{}", header.join("\t"));
+
+    for (i, row) in report.banks.iter().enumerate() {
+        let row: Vec<String> = row.iter().map(ToString::to_string).collect();
+        println!("{}\t{}", i + 1, row.join("\t"));
+    }
+
+    let totals: Vec<String> = report.totals.iter().map(ToString::to_string).collect();
+    println!("total\t{}", totals.join("\t"));
+
+    Ok(())
+}
+
+fn printing_department_command(mut args: Vec<String>) -> Result<(), String> {
+    let input = read_input(take_option(&mut args, "--input")?, printing_department::RAW)?;
+    let neighborhood = match take_option(&mut args, "--neighborhood")? {
+        Some(neighborhood) => neighborhood
+            .parse()
+            .map_err(|e| format!("{e}: `{neighborhood}`"))?,
+        None => printing_department::Neighborhood::default(),
+    };
+    let rule = printing_department::AccessRule {
+        neighborhood,
+        threshold: parse_count(take_option(&mut args, "--threshold")?, 4)?,
+    };
+    let boundary = match take_option(&mut args, "--boundary")? {
+        Some(boundary) => boundary.parse().map_err(|e| format!("{e}: `{boundary}`"))?,
+        None => printing_department::Boundary::default(),
+    };
+    let waves = take_flag(&mut args, "--waves");
+    let rounds = take_flag(&mut args, "--rounds");
+    let export = take_option(&mut args, "--export")?;
+    let scale = parse_count(take_option(&mut args, "--scale")?, 4)?;
+    let packed = take_flag(&mut args, "--packed");
+    ensure_consumed(&args)?;
+
+    if packed {
+        if rule != printing_department::AccessRule::default()
+            || boundary != printing_department::Boundary::default()
+        {
+            return Err(
+                "--packed only supports the default neighborhood, threshold and boundary"
+                    .to_owned(),
+            );
+        }
+
+        if waves || rounds || export.is_some() {
+            return Err(
+                "--packed cannot be combined with --waves, --rounds or --export".to_owned(),
+            );
+        }
+
+        let grid: printing_department::BitGrid = input.parse().map_err(|e: &str| e.to_owned())?;
+        let stats = grid.simulate();
+        println!("accessible\t{}", stats.accessible);
+        println!("total removable\t{}", stats.total_removable);
+
+        return Ok(());
+    }
+
+    let grid = printing_department::HeapGrid::parse_with(&input, &rule)?.with_boundary(boundary);
+
+    if let Some(dir) = export {
+        let timeline = printing_department::run_waves(grid);
+        fs::create_dir_all(&dir).map_err(|e| format!("{dir}: {e}"))?;
+
+        let create = |name: String| -> Result<BufWriter<File>, String> {
+            let path = std::path::Path::new(&dir).join(name);
+            File::create(&path)
+                .map(BufWriter::new)
+                .map_err(|e| format!("{}: {e}", path.display()))
+        };
+
+        for frame in timeline.frames() {
+            let name = match frame {
+                printing_department::Frame::Initial => "initial".to_owned(),
+                printing_department::Frame::Wave(n) => format!("wave-{n:03}"),
+                printing_department::Frame::Final => "final".to_owned(),
+            };
+
+            let mut ppm = create(format!("{name}.ppm"))?;
+            let mut pgm = create(format!("{name}.pgm"))?;
+            timeline
+                .write_ppm(&mut ppm, frame, scale)
+                .and_then(|()| timeline.write_pgm(&mut pgm, frame, scale))
+                .and_then(|()| ppm.flush())
+                .and_then(|()| pgm.flush())
+                .map_err(|e| e.to_string())?;
+        }
+
+        let mut animation = create("animation.ppm".to_owned())?;
+        let frames = timeline
+            .write_animation(&mut animation, scale)
+            .and_then(|frames| animation.flush().map(|()| frames))
+            .map_err(|e| e.to_string())?;
+        println!("wrote {frames} frames to {dir}");
+
+        return Ok(());
+    }
+
+    if waves || rounds {
+        let timeline = printing_department::run_waves(grid);
+        let mut out = open_output(None)?;
+
+        if waves {
+            writeln!(out, "round\tremoved\tcells").map_err(|e| e.to_string())?;
+            for (round, wave) in timeline.waves.iter().enumerate() {
+                let cells: Vec<String> = wave.iter().map(|(x, y)| format!("{x},{y}")).collect();
+                writeln!(out, "{}\t{}\t{}", round + 1, wave.len(), cells.join(" "))
+                    .map_err(|e| e.to_string())?;
+            }
+            writeln!(out, "total\t{}", timeline.total_removed()).map_err(|e| e.to_string())?;
+        }
+
+        if rounds {
+            writeln!(out, "x\ty\tround").map_err(|e| e.to_string())?;
+            for ((x, y), round) in timeline.rolls() {
+                let round = round.map_or("-".to_owned(), |round| round.to_string());
+                writeln!(out, "{x}\t{y}\t{round}").map_err(|e| e.to_string())?;
+            }
+        }
+
+        return out.flush().map_err(|e| e.to_string());
+    }
+
+    println!("floor\t{}x{}", grid.floor_width(), grid.floor_height());
+
+    let stats = printing_department::run_simulation(grid);
+    println!("accessible\t{}", stats.accessible);
+    println!("total removable\t{}", stats.total_removable);
+
+    Ok(())
+}
+
+/// Maximum joltages of every bank before and after each of `edits`, applied
+/// in turn to a [`lobby::DynamicBatteryBank`].
+fn lobby_edits(
+    banks: &[lobby::BatteryBank],
+    edits: &[lobby::BankEdit],
+    sizes: &[usize],
+) -> Result<(), String> {
+    let header: Vec<String> = sizes.iter().map(|k| format!("k={k}")).collect();
+    println!("bank\tedit\twas\tcells\t{}", header.join("\t"));
+
+    for (i, bank) in banks.iter().enumerate() {
+        let mut dynamic = lobby::DynamicBatteryBank::from(bank.clone());
+        let row = |dynamic: &lobby::DynamicBatteryBank| -> String {
+            let joltages: Vec<String> = sizes
+                .iter()
+                .map(|&k| dynamic.max_joltage(k).to_string())
+                .collect();
+
+            format!("{}\t{}", dynamic.len(), joltages.join("\t"))
+        };
+
+        println!("{}\t-\t-\t{}", i + 1, row(&dynamic));
+        for (step, &edit) in edits.iter().enumerate() {
+            let was = match edit {
+                lobby::BankEdit::Replace { index, .. } | lobby::BankEdit::Remove { index } => {
+                    dynamic
+                        .get(index)
+                        .map(|battery| battery.joltage.to_string())
+                }
+                lobby::BankEdit::Insert { .. } => None,
+            };
+
+            dynamic
+                .apply(edit)
+                .map_err(|e| format!("bank {}, edit {}: {e}", i + 1, step + 1))?;
+            println!(
+                "{}\t{}\t{}\t{}",
+                i + 1,
+                step + 1,
+                was.as_deref().unwrap_or("-"),
+                row(&dynamic)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Best selections within each `l..=r` of `cells`, answered from one
+/// [`lobby::RangeIndex`] per bank.
+fn lobby_cells(
+    banks: &[lobby::BatteryBank],
+    cells: &[(usize, usize)],
+    sizes: &[usize],
+    show: bool,
+) -> Result<(), String> {
+    if !show {
+        let header: Vec<String> = sizes.iter().map(|k| format!("k={k}")).collect();
+        println!("bank\tcells\t{}", header.join("\t"));
+    }
+
+    for (i, bank) in banks.iter().enumerate() {
+        let index: lobby::RangeIndex = bank.range_index();
+
+        for &(l, r) in cells {
+            if show {
+                for &k in sizes {
+                    match index.select(l, r, k) {
+                        Some(selection) => {
+                            println!("bank {} cells {l}-{r} k={k}: {}", i + 1, selection.joltage);
+                            println!("{}\n", bank.render_selection(&selection));
+                        }
+                        None => {
+                            println!("bank {} cells {l}-{r} k={k}: no valid selection\n", i + 1)
+                        }
+                    }
+                }
+
+                continue;
+            }
+
+            let row: Vec<String> = sizes
+                .iter()
+                .map(|&k| {
+                    index
+                        .max_joltage(l, r, k)
+                        .map_or_else(|| "-".to_owned(), |joltage| joltage.to_string())
+                })
+                .collect();
+            println!("{}\t{l}-{r}\t{}", i + 1, row.join("\t"));
+        }
+    }
+
+    Ok(())
+}
+
+fn lobby_stream(mut args: Vec<String>) -> Result<(), String> {
+    let sizes = parse_sizes(
+        take_option(&mut args, "--select")?
+            .as_deref()
+            .unwrap_or("2,12"),
+    )?;
+    let reader = open_reader(args.pop_if(|arg| !arg.starts_with("--")))?;
+    ensure_consumed(&args)?;
+
+    let selections = lobby::select_streaming(reader, &sizes).map_err(|e| e.to_string())?;
+
+    println!("k\tjoltage");
+    for (k, selection) in sizes.iter().zip(selections) {
+        match selection {
+            Some(selection) => println!("{k}\t{}", selection.joltage),
+            None => println!("{k}\t-"),
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_count(value: Option<String>, default: usize) -> Result<usize, String> {
+    match value {
+        Some(value) => value
+            .parse()
+            .map_err(|_| format!("invalid count `{value}`")),
+        None => Ok(default),
+    }
+}
+
+fn parse_sizes(list: &str) -> Result<Vec<usize>, String> {
+    list.split(',')
+        .map(|k| {
+            k.trim()
+                .parse()
+                .map_err(|_| format!("invalid selection size `{k}`"))
+        })
+        .collect()
+}
+
+/// Parses `l-r,...` into inclusive, 0-based cell ranges.
+fn parse_cells(list: &str) -> Result<Vec<(usize, usize)>, String> {
+    list.split(',')
+        .map(|range| {
+            let invalid = || format!("invalid cell range `{range}`");
+            let (l, r) = range.trim().split_once('-').ok_or_else(invalid)?;
+            let l: usize = l.parse().map_err(|_| invalid())?;
+            let r: usize = r.parse().map_err(|_| invalid())?;
+
+            if l > r {
+                return Err(invalid());
+            }
+
+            Ok((l, r))
+        })
+        .collect()
+}
+
+fn print_gift_shop_report(report: &gift_shop::GiftShopReport) {
+    println!("{:<40} {:>20} {:>20}", "range", "identical", "repeating");
+    for range in &report.ranges {
+        let name = match &range.label {
+            Some(label) => format!("{label}: {}-{}", range.start, range.end),
+            None => format!("{}-{}", range.start, range.end),
+        };
+
+        println!(
+            "{:<40} {:>20} {:>20}",
+            name, range.totals.identical_halves_sum, range.totals.repeating_patterns_sum
+        );
+    }
+
+    println!();
+    println!(
+        "{:>6} {:>11} {:>6} {:>8} {:>20}",
+        "digits", "repetitions", "period", "count", "sum"
+    );
+    for (class, tally) in &report.breakdown.classes {
+        println!(
+            "{:>6} {:>11} {:>6} {:>8} {:>20}",
+            class.digits, class.repetitions, class.period, tally.count, tally.sum
+        );
+    }
+
+    let classes = report.breakdown.classes.keys();
+    let mut digits: Vec<usize> = classes.clone().map(|class| class.digits).collect();
+    let mut repetitions: Vec<usize> = classes.map(|class| class.repetitions).collect();
+    digits.dedup();
+    repetitions.sort_unstable();
+    repetitions.dedup();
+
+    println!();
+    println!("{:>6} {:>8} {:>20}", "digits", "count", "sum");
+    for digits in digits {
+        let tally = report.breakdown.by_digits(digits);
+        println!("{digits:>6} {:>8} {:>20}", tally.count, tally.sum);
+    }
+
+    println!();
+    println!("{:>11} {:>8} {:>20}", "repetitions", "count", "sum");
+    for repetitions in repetitions {
+        let tally = report.breakdown.by_repetitions(repetitions);
+        println!("{repetitions:>11} {:>8} {:>20}", tally.count, tally.sum);
+    }
+
+    println!();
+    println!(
+        "identical halves sum:  {}",
+        report.totals.identical_halves_sum
+    );
+    println!(
+        "repeating pattern sum: {}",
+        report.totals.repeating_patterns_sum
+    );
+}
+
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, String> {
+    let Some(pos) = args.iter().position(|arg| arg == name) else {
+        return Ok(None);
+    };
+
+    if pos + 1 >= args.len() {
+        return Err(format!("`{name}` expects a value"));
+    }
+
+    let value = args.remove(pos + 1);
+    args.remove(pos);
+
+    Ok(Some(value))
+}
+
+fn take_flag(args: &mut Vec<String>, name: &str) -> bool {
+    match args.iter().position(|arg| arg == name) {
+        Some(pos) => {
+            args.remove(pos);
+            true
+        }
+        None => false,
+    }
+}
+
+fn ensure_consumed(args: &[String]) -> Result<(), String> {
+    match args.first() {
+        Some(arg) => Err(format!("unexpected argument `{arg}`\n\n{USAGE}")),
+        None => Ok(()),
+    }
+}
+
+fn read_input(path: Option<String>, bundled: &str) -> Result<String, String> {
+    match path {
+        Some(path) => fs::read_to_string(&path).map_err(|e| format!("{path}: {e}")),
+        None => Ok(bundled.to_owned()),
+    }
+}
+
+fn read_ranges(path: Option<String>) -> Result<Vec<gift_shop::ProductRange>, String> {
+    let input = read_input(path, gift_shop::INPUT)?;
+
+    gift_shop::parse_ranges(&input).map_err(|e| e.to_string())
+}
+
+fn open_reader(path: Option<String>) -> Result<Box<dyn BufRead>, String> {
+    match path.as_deref() {
+        None | Some("-") => Ok(Box::new(io::stdin().lock())),
+        Some(path) => {
+            let file = File::open(path).map_err(|e| format!("{path}: {e}"))?;
+            Ok(Box::new(BufReader::new(file)))
+        }
+    }
+}
+
+fn open_output(path: Option<String>) -> Result<Box<dyn Write>, String> {
+    match path {
+        Some(path) => {
+            let file = File::create(&path).map_err(|e| format!("{path}: {e}"))?;
+            Ok(Box::new(BufWriter::new(file)))
+        }
+        None => Ok(Box::new(BufWriter::new(io::stdout().lock()))),
+    }
+}
@@ -1,13 +1,52 @@
+use std::num::NonZeroUsize;
 use std::ops::AddAssign;
+use std::thread;
 
-const INPUT: &str = include_str!("input.txt");
+mod navigation;
+mod parallel;
+mod ranges;
+mod report;
+mod rules;
 
-#[derive(Debug, Default, Clone, Copy)]
+pub use navigation::{next_repeating_pattern, next_valid_id, previous_repeating_pattern};
+pub use parallel::scan_parallel;
+pub use ranges::{ProductRange, RangeParseError, find_overlaps, merge_ranges, parse_ranges};
+pub use report::{GiftShopReport, build_report};
+pub use rules::{
+    DigitTemplate, IdRule, IdenticalHalves, Palindrome, RepeatedChunk, RuleSet, UniformDigits,
+};
+
+use rules::RuleMatch;
+
+pub const INPUT: &str = include_str!("input.txt");
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
 pub struct InvalidProductIds {
     pub identical_halves_sum: usize,
     pub repeating_patterns_sum: usize,
 }
 
+impl InvalidProductIds {
+    pub fn record(&mut self, pattern: &RepeatingPattern) {
+        self.repeating_patterns_sum += pattern.id;
+
+        if pattern.has_identical_halves() {
+            self.identical_halves_sum += pattern.id;
+        }
+    }
+
+    /// Adds an id flagged by [`RuleSet::builtin`].
+    fn record_builtin(&mut self, found: &RuleMatch) {
+        for &rule in &found.rules {
+            match rule {
+                RuleSet::IDENTICAL_HALVES => self.identical_halves_sum += found.id,
+                RuleSet::REPEATED_CHUNK => self.repeating_patterns_sum += found.id,
+                _ => unreachable!("the builtin rule set has two rules"),
+            }
+        }
+    }
+}
+
 impl AddAssign for InvalidProductIds {
     fn add_assign(&mut self, rhs: Self) {
         self.identical_halves_sum += rhs.identical_halves_sum;
@@ -18,101 +57,245 @@ impl AddAssign for InvalidProductIds {
 #[inline]
 #[must_use]
 pub fn solve() -> InvalidProductIds {
-    run_simulation(INPUT)
+    run_simulation(INPUT).expect("bundled input is a valid range list")
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ScanOptions {
+    /// Sort and merge the input ranges first so ids covered by several
+    /// ranges are only counted once.
+    pub merge_overlaps: bool,
+    /// Spread the scan across every available core.
+    pub parallel: bool,
+}
+
+pub fn run_simulation(input: &str) -> Result<InvalidProductIds, RangeParseError> {
+    run_simulation_with(input, ScanOptions::default())
+}
+
+pub fn run_simulation_with(
+    input: &str,
+    options: ScanOptions,
+) -> Result<InvalidProductIds, RangeParseError> {
+    let ranges = parse_ranges(input)?;
+
+    Ok(scan(&ranges, options))
 }
 
 #[must_use]
-fn run_simulation(input: &str) -> InvalidProductIds {
-    let mut total = InvalidProductIds::default();
+pub fn scan(ranges: &[ProductRange], options: ScanOptions) -> InvalidProductIds {
+    let ranges = scan_bounds(ranges, options);
 
-    for (start, end) in parse_ranges(input) {
+    if options.parallel {
+        let threads = thread::available_parallelism().map_or(1, NonZeroUsize::get);
+        return scan_parallel(&ranges, threads);
+    }
+
+    let mut total = InvalidProductIds::default();
+    for (start, end) in ranges {
         total += calculate_range_metrics(start, end);
     }
 
     total
 }
 
+/// The bounds to scan for `ranges`, honouring `options`.
+pub fn scan_bounds(ranges: &[ProductRange], options: ScanOptions) -> Vec<(usize, usize)> {
+    let bounds = ranges.iter().map(ProductRange::bounds);
+
+    if options.merge_overlaps {
+        merge_ranges(bounds)
+    } else {
+        bounds.collect()
+    }
+}
+
 fn calculate_range_metrics(start: usize, end: usize) -> InvalidProductIds {
     let mut metrics = InvalidProductIds::default();
 
-    if start > end {
-        return metrics;
+    for found in RuleSet::builtin().matches(start, end) {
+        metrics.record_builtin(&found);
     }
 
-    let min_digits = if start == 0 {
-        1
-    } else {
-        start.ilog10() as usize + 1
-    };
-    let max_digits = if end == 0 {
-        1
-    } else {
-        end.ilog10() as usize + 1
-    };
+    metrics
+}
+
+/// A product ID made of `seed` written out `repetitions` times.
+///
+/// The seed is always the shortest chunk that reproduces the id, so `111111`
+/// is reported as `1` repeated six times rather than `111` repeated twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RepeatingPattern {
+    pub id: usize,
+    pub seed: usize,
+    pub repetitions: usize,
+}
+
+impl RepeatingPattern {
+    /// Number of digits in the primitive chunk.
+    #[inline]
+    pub fn period(&self) -> usize {
+        digit_count(self.seed)
+    }
+
+    /// Number of digits in the whole id.
+    #[inline]
+    pub fn digits(&self) -> usize {
+        self.period() * self.repetitions
+    }
+
+    /// Whether the id is the same chunk written twice, which is exactly when
+    /// its primitive chunk is repeated an even number of times.
+    #[inline]
+    pub fn has_identical_halves(&self) -> bool {
+        self.repetitions.is_multiple_of(2)
+    }
+}
+
+/// Checks a single id, returning its primitive decomposition when it is made
+/// of a chunk repeated at least twice.
+pub fn is_repeating_pattern(id: usize) -> Option<RepeatingPattern> {
+    let len = digit_count(id);
+
+    // trying chunk lengths in increasing order means the first hit is the
+    // minimal period.
+    for chunk_len in 1..len {
+        if !len.is_multiple_of(chunk_len) {
+            continue;
+        }
+
+        let repetitions = len / chunk_len;
+        let multiplier = generate_repetition_multiplier(chunk_len, repetitions);
+        if !id.is_multiple_of(multiplier) {
+            continue;
+        }
+
+        let seed = id / multiplier;
+        if seed >= 10usize.pow(chunk_len as u32 - 1) {
+            return Some(RepeatingPattern {
+                id,
+                seed,
+                repetitions,
+            });
+        }
+    }
+
+    None
+}
+
+/// Lazily enumerates every repeating-pattern ID in `start..=end`, in ascending
+/// order and without duplicates.
+pub fn repeating_patterns(start: usize, end: usize) -> RepeatingPatterns {
+    RepeatingPatterns {
+        start,
+        end,
+        digits: digit_count(start),
+        max_digits: if start > end { 0 } else { digit_count(end) },
+        generators: Vec::new(),
+    }
+}
 
-    let mut found_patterns = Vec::new();
-    for len in min_digits..=max_digits {
-        for parts in 2..=len {
-            if len % parts != 0 {
+#[derive(Debug, Clone)]
+pub struct RepeatingPatterns {
+    start: usize,
+    end: usize,
+    digits: usize,
+    max_digits: usize,
+    generators: Vec<PatternGenerator>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct PatternGenerator {
+    multiplier: usize,
+    repetitions: usize,
+    next_seed: usize,
+    last_seed: usize,
+}
+
+impl RepeatingPatterns {
+    fn load_generators(&mut self, len: usize) {
+        // generators are pushed by increasing chunk length, which is what
+        // lets `next` pick the primitive chunk when several of them collide.
+        for chunk_len in 1..len {
+            if !len.is_multiple_of(chunk_len) {
                 continue;
             }
 
-            let chunk_len = len / parts;
-            let multiplier = generate_repetition_multiplier(chunk_len, parts);
+            let repetitions = len / chunk_len;
+            let multiplier = generate_repetition_multiplier(chunk_len, repetitions);
 
             let seed_min_digits = 10usize.pow(chunk_len as u32 - 1);
             let seed_max_digits = 10usize.pow(chunk_len as u32) - 1;
 
-            let seed_start = start.div_ceil(multiplier);
-            let seed_end = end / multiplier;
+            let next_seed = self.start.div_ceil(multiplier).max(seed_min_digits);
+            let last_seed = (self.end / multiplier).min(seed_max_digits);
+
+            if next_seed <= last_seed {
+                self.generators.push(PatternGenerator {
+                    multiplier,
+                    repetitions,
+                    next_seed,
+                    last_seed,
+                });
+            }
+        }
+    }
+}
 
-            let valid_start = seed_start.max(seed_min_digits);
-            let valid_end = seed_end.min(seed_max_digits);
+impl Iterator for RepeatingPatterns {
+    type Item = RepeatingPattern;
 
-            if valid_start > valid_end {
-                continue;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.generators.is_empty() {
+            if self.digits > self.max_digits {
+                return None;
             }
 
-            for seed in valid_start..=valid_end {
-                let n = seed * multiplier;
+            self.load_generators(self.digits);
+            self.digits += 1;
+        }
 
-                found_patterns.push(n);
+        let mut best = 0;
+        for (i, generator) in self.generators.iter().enumerate().skip(1) {
+            let current = &self.generators[best];
+            if generator.next_seed * generator.multiplier < current.next_seed * current.multiplier {
+                best = i;
+            }
+        }
 
-                if parts == 2 {
-                    metrics.identical_halves_sum += n;
-                }
+        let PatternGenerator {
+            multiplier,
+            repetitions,
+            next_seed: seed,
+            ..
+        } = self.generators[best];
+        let id = seed * multiplier;
+
+        for generator in &mut self.generators {
+            if generator.next_seed * generator.multiplier == id {
+                generator.next_seed += 1;
             }
         }
-    }
+        self.generators.retain(|g| g.next_seed <= g.last_seed);
 
-    found_patterns.sort_unstable();
-    found_patterns.dedup();
-    metrics.repeating_patterns_sum = found_patterns.iter().sum();
+        Some(RepeatingPattern {
+            id,
+            seed,
+            repetitions,
+        })
+    }
+}
 
-    metrics
+#[inline]
+fn digit_count(n: usize) -> usize {
+    if n == 0 { 1 } else { n.ilog10() as usize + 1 }
 }
 
 #[inline(always)]
 fn generate_repetition_multiplier(len: usize, count: usize) -> usize {
-    let mut multiplier = 0;
     let step = 10usize.pow(len as u32);
-    let mut current = 1;
 
-    for _ in 0..count {
-        multiplier += current;
-        current *= step;
-    }
-
-    multiplier
-}
-
-fn parse_ranges(input: &str) -> impl Iterator<Item = (usize, usize)> + '_ {
-    input.split(',').filter_map(|part| {
-        let (s, e) = part.split_once('-')?;
-        let start = s.trim().parse().ok()?;
-        let end = e.trim().parse().ok()?;
-        Some((start, end))
-    })
+    (0..count).fold(0, |multiplier, _| multiplier * step + 1)
 }
 
 #[cfg(test)]
@@ -122,7 +305,7 @@ mod tests {
     #[test]
     fn test_identical_halves_sum_example() {
         let input = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
-        let result = run_simulation(input);
+        let result = run_simulation(input).unwrap();
 
         assert_eq!(result.identical_halves_sum, 1_227_775_554);
     }
@@ -137,7 +320,7 @@ mod tests {
     #[test]
     fn test_repeating_patterns_sum_example() {
         let input = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
-        let result = run_simulation(input);
+        let result = run_simulation(input).unwrap();
 
         assert_eq!(result.repeating_patterns_sum, 4_174_379_265);
     }
@@ -148,4 +331,136 @@ mod tests {
 
         assert_eq!(result.repeating_patterns_sum, 41_823_587_546);
     }
+
+    #[test]
+    fn test_merge_overlaps() {
+        let options = ScanOptions {
+            merge_overlaps: true,
+            ..ScanOptions::default()
+        };
+
+        let overlapping = run_simulation("10-50,40-90").unwrap();
+        let merged = run_simulation_with("10-50,40-90", options).unwrap();
+
+        assert_eq!(
+            overlapping.identical_halves_sum,
+            11 + 22 + 33 + 44 * 2 + 55 + 66 + 77 + 88
+        );
+        assert_eq!(merged, run_simulation("10-90").unwrap());
+        assert_eq!(run_simulation_with(INPUT, options).unwrap(), solve());
+    }
+
+    #[test]
+    fn test_invalid_range_list() {
+        let error = run_simulation("11-22,95-x").unwrap_err();
+
+        assert_eq!(error.entry, "95-x");
+    }
+
+    #[test]
+    fn test_open_ended_range() {
+        assert_eq!(
+            run_simulation("5-").unwrap(),
+            run_simulation("5-9").unwrap()
+        );
+        assert_eq!(
+            run_simulation("500-,11-").unwrap(),
+            run_simulation("500-999,11-99").unwrap()
+        );
+        assert_eq!(
+            run_simulation("1000-").unwrap().identical_halves_sum,
+            495_405
+        );
+    }
+
+    #[test]
+    fn test_parallel_option() {
+        let options = ScanOptions {
+            merge_overlaps: true,
+            parallel: true,
+        };
+
+        assert_eq!(run_simulation_with(INPUT, options).unwrap(), solve());
+    }
+
+    #[test]
+    fn test_repeating_patterns_example() {
+        let ids: Vec<_> = repeating_patterns(95, 1012)
+            .map(|p| (p.id, p.seed, p.repetitions))
+            .collect();
+
+        assert_eq!(
+            ids,
+            vec![
+                (99, 9, 2),
+                (111, 1, 3),
+                (222, 2, 3),
+                (333, 3, 3),
+                (444, 4, 3),
+                (555, 5, 3),
+                (666, 6, 3),
+                (777, 7, 3),
+                (888, 8, 3),
+                (999, 9, 3),
+                (1010, 10, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_repeating_patterns_primitive_seed() {
+        let ids: Vec<_> = repeating_patterns(111_111, 111_111).collect();
+
+        assert_eq!(
+            ids,
+            vec![RepeatingPattern {
+                id: 111_111,
+                seed: 1,
+                repetitions: 6
+            }]
+        );
+    }
+
+    #[test]
+    fn test_is_repeating_pattern() {
+        assert_eq!(
+            is_repeating_pattern(824_824_824),
+            Some(RepeatingPattern {
+                id: 824_824_824,
+                seed: 824,
+                repetitions: 3
+            })
+        );
+        assert_eq!(is_repeating_pattern(222_222).map(|p| p.period()), Some(1));
+        assert_eq!(is_repeating_pattern(1010).map(|p| p.period()), Some(2));
+        assert_eq!(is_repeating_pattern(1001), None);
+        assert_eq!(is_repeating_pattern(7), None);
+        assert_eq!(is_repeating_pattern(0), None);
+        assert_eq!(is_repeating_pattern(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_is_repeating_pattern_agrees_with_iterator() {
+        let mut patterns = repeating_patterns(0, 200_000).peekable();
+
+        for id in 0..=200_000 {
+            let expected = patterns.next_if(|p| p.id == id);
+
+            assert_eq!(is_repeating_pattern(id), expected);
+        }
+    }
+
+    #[test]
+    fn test_repeating_patterns_brute_force() {
+        let expected: Vec<usize> = (0..=200_000)
+            .filter(|id| {
+                let s = id.to_string();
+                (1..s.len())
+                    .any(|chunk| s.len() % chunk == 0 && s[..chunk].repeat(s.len() / chunk) == s)
+            })
+            .collect();
+        let ids: Vec<usize> = repeating_patterns(0, 200_000).map(|p| p.id).collect();
+
+        assert_eq!(ids, expected);
+    }
 }
@@ -0,0 +1,136 @@
+use super::{RepeatingPattern, digit_count, generate_repetition_multiplier, is_repeating_pattern};
+
+/// Number of digits in `usize::MAX`.
+const MAX_DIGITS: usize = 20;
+
+/// Smallest repeating-pattern id that is at least `n`.
+pub fn next_repeating_pattern(n: usize) -> Option<RepeatingPattern> {
+    for len in digit_count(n).max(2)..=MAX_DIGITS {
+        let mut best: Option<RepeatingPattern> = None;
+
+        for (chunk_len, repetitions, multiplier) in generators(len) {
+            let seed = n
+                .div_ceil(multiplier)
+                .max(10usize.pow(chunk_len as u32 - 1));
+            if seed >= 10usize.pow(chunk_len as u32) {
+                continue;
+            }
+
+            let Some(id) = seed.checked_mul(multiplier) else {
+                continue;
+            };
+
+            // strict comparison keeps the shortest chunk when several
+            // generators land on the same id.
+            if best.is_none_or(|b| id < b.id) {
+                best = Some(RepeatingPattern {
+                    id,
+                    seed,
+                    repetitions,
+                });
+            }
+        }
+
+        if best.is_some() {
+            return best;
+        }
+    }
+
+    None
+}
+
+/// Largest repeating-pattern id that is at most `n`.
+pub fn previous_repeating_pattern(n: usize) -> Option<RepeatingPattern> {
+    for len in (2..=digit_count(n)).rev() {
+        let mut best: Option<RepeatingPattern> = None;
+
+        for (chunk_len, repetitions, multiplier) in generators(len) {
+            let seed = (n / multiplier).min(10usize.pow(chunk_len as u32) - 1);
+            if seed < 10usize.pow(chunk_len as u32 - 1) {
+                continue;
+            }
+
+            let id = seed * multiplier;
+            if best.is_none_or(|b| id > b.id) {
+                best = Some(RepeatingPattern {
+                    id,
+                    seed,
+                    repetitions,
+                });
+            }
+        }
+
+        if best.is_some() {
+            return best;
+        }
+    }
+
+    None
+}
+
+/// Smallest id greater than `n` that is not a repeating pattern.
+pub fn next_valid_id(n: usize) -> Option<usize> {
+    let mut candidate = n.checked_add(1)?;
+
+    while is_repeating_pattern(candidate).is_some() {
+        candidate = candidate.checked_add(1)?;
+    }
+
+    Some(candidate)
+}
+
+/// `(chunk_len, repetitions, multiplier)` for every way of splitting a
+/// `len`-digit id into at least two chunks, shortest chunk first.
+fn generators(len: usize) -> impl Iterator<Item = (usize, usize, usize)> {
+    (1..len)
+        .filter(move |chunk_len| len.is_multiple_of(*chunk_len))
+        .map(move |chunk_len| {
+            let repetitions = len / chunk_len;
+            let multiplier = generate_repetition_multiplier(chunk_len, repetitions);
+
+            (chunk_len, repetitions, multiplier)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gift_shop::repeating_patterns;
+
+    #[test]
+    fn test_navigation_examples() {
+        assert_eq!(next_repeating_pattern(0).map(|p| p.id), Some(11));
+        assert_eq!(next_repeating_pattern(100).map(|p| p.id), Some(111));
+        assert_eq!(next_repeating_pattern(1011).map(|p| p.id), Some(1111));
+        assert_eq!(previous_repeating_pattern(10), None);
+        assert_eq!(previous_repeating_pattern(1109).map(|p| p.id), Some(1010));
+        assert_eq!(next_valid_id(10), Some(12));
+        assert_eq!(next_valid_id(21), Some(23));
+        assert_eq!(next_valid_id(98), Some(100));
+    }
+
+    #[test]
+    fn test_navigation_at_usize_limits() {
+        let largest = previous_repeating_pattern(usize::MAX).unwrap();
+
+        assert_eq!(largest.id, 18_446_744_071_844_674_407);
+        assert_eq!(is_repeating_pattern(largest.id), Some(largest));
+        assert_eq!(next_repeating_pattern(largest.id + 1), None);
+        assert_eq!(next_valid_id(usize::MAX), None);
+    }
+
+    #[test]
+    fn test_navigation_agrees_with_iterator() {
+        let patterns: Vec<_> = repeating_patterns(0, 120_000).collect();
+
+        for n in 0..=110_000 {
+            let next = patterns.iter().find(|p| p.id >= n).copied();
+            let previous = patterns.iter().rev().find(|p| p.id <= n).copied();
+            let valid = (n + 1..).find(|&id| is_repeating_pattern(id).is_none());
+
+            assert_eq!(next_repeating_pattern(n), next, "next {n}");
+            assert_eq!(previous_repeating_pattern(n), previous, "previous {n}");
+            assert_eq!(next_valid_id(n), valid, "valid {n}");
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use std::thread;
+
+use super::{InvalidProductIds, calculate_range_metrics, digit_count};
+
+/// Scans `ranges` on up to `threads` threads.
+///
+/// Ranges are first cut at digit-length boundaries so a single huge range
+/// still spreads across threads. No repeating pattern straddles two bands,
+/// so the per-band metrics add up to exactly what the sequential scan finds.
+pub fn scan_parallel(ranges: &[(usize, usize)], threads: usize) -> InvalidProductIds {
+    let bands: Vec<(usize, usize)> = ranges
+        .iter()
+        .flat_map(|&(start, end)| digit_bands(start, end))
+        .collect();
+
+    let threads = threads.clamp(1, bands.len().max(1));
+    if threads == 1 {
+        return scan_bands(bands.iter().copied());
+    }
+
+    thread::scope(|scope| {
+        let workers: Vec<_> = (0..threads)
+            .map(|worker| {
+                let bands = &bands;
+                scope.spawn(move || scan_bands(bands.iter().skip(worker).step_by(threads).copied()))
+            })
+            .collect();
+
+        let mut total = InvalidProductIds::default();
+        for worker in workers {
+            total += worker.join().expect("gift shop worker panicked");
+        }
+
+        total
+    })
+}
+
+fn scan_bands(bands: impl Iterator<Item = (usize, usize)>) -> InvalidProductIds {
+    let mut total = InvalidProductIds::default();
+    for (start, end) in bands {
+        total += calculate_range_metrics(start, end);
+    }
+
+    total
+}
+
+/// Splits `start..=end` into sub-ranges whose ids all have the same number
+/// of digits.
+fn digit_bands(start: usize, end: usize) -> impl Iterator<Item = (usize, usize)> {
+    let lengths = if start > end {
+        0..0
+    } else {
+        digit_count(start)..digit_count(end) + 1
+    };
+
+    lengths.map(move |len| {
+        let band_start = if len == 1 {
+            0
+        } else {
+            10usize.pow(len as u32 - 1)
+        };
+        let band_end = 10usize
+            .checked_pow(len as u32)
+            .map_or(usize::MAX, |limit| limit - 1);
+
+        (start.max(band_start), end.min(band_end))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gift_shop::{INPUT, ScanOptions, parse_ranges, run_simulation, scan_bounds};
+
+    #[test]
+    fn test_digit_bands() {
+        let bands: Vec<_> = digit_bands(95, 12_345).collect();
+
+        assert_eq!(
+            bands,
+            vec![(95, 99), (100, 999), (1000, 9999), (10_000, 12_345)]
+        );
+        assert_eq!(digit_bands(5, 3).count(), 0);
+        assert_eq!(
+            digit_bands(usize::MAX - 1, usize::MAX).collect::<Vec<_>>(),
+            vec![(usize::MAX - 1, usize::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_parallel_matches_sequential() {
+        let example = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124,1-99999999";
+
+        for input in [example, INPUT] {
+            let ranges = scan_bounds(&parse_ranges(input).unwrap(), ScanOptions::default());
+
+            for threads in [1, 2, 3, 8] {
+                assert_eq!(
+                    scan_parallel(&ranges, threads),
+                    run_simulation(input).unwrap()
+                );
+            }
+        }
+    }
+}
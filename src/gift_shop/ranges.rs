@@ -0,0 +1,300 @@
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use super::digit_count;
+
+/// One entry of a range list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProductRange {
+    pub label: Option<String>,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeParseError {
+    /// 1-based line of the offending entry.
+    pub line: usize,
+    pub entry: String,
+    pub reason: &'static str,
+}
+
+/// Two input ranges that share at least one id.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeOverlap {
+    /// Position of the earlier range in the input.
+    pub first: usize,
+    /// Position of the later range in the input.
+    pub second: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Parses a range list.
+///
+/// Entries are separated by commas or newlines and `#` starts a comment that
+/// runs to the end of the line. Each entry may be prefixed with a label
+/// (`warehouse-a: 100-200`) and is one of:
+///
+/// - `100-200`, every id from 100 to 200;
+/// - `500-`, an open-ended range that runs to the last id with as many
+///   digits as its start, so `500-` covers 500 to 999 and a 20-digit start
+///   runs to `usize::MAX`;
+/// - `1234`, a single id.
+pub fn parse_ranges(input: &str) -> Result<Vec<ProductRange>, RangeParseError> {
+    let mut ranges = Vec::new();
+
+    for (index, line) in input.lines().enumerate() {
+        let content = line.split_once('#').map_or(line, |(content, _)| content);
+
+        for entry in content.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let range = entry.parse().map_err(|reason| RangeParseError {
+                line: index + 1,
+                entry: entry.to_owned(),
+                reason,
+            })?;
+
+            ranges.push(range);
+        }
+    }
+
+    Ok(ranges)
+}
+
+/// Sorts the ranges and merges the ones that overlap or touch, so every id is
+/// covered by exactly one range. Empty ranges are dropped.
+pub fn merge_ranges(ranges: impl IntoIterator<Item = (usize, usize)>) -> Vec<(usize, usize)> {
+    let mut ranges: Vec<_> = ranges
+        .into_iter()
+        .filter(|(start, end)| start <= end)
+        .collect();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, current_end)) if start <= current_end.saturating_add(1) => {
+                *current_end = (*current_end).max(end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+
+    merged
+}
+
+/// Lists every pair of input ranges that share ids, ordered by input
+/// position.
+pub fn find_overlaps(ranges: &[(usize, usize)]) -> Vec<RangeOverlap> {
+    let mut order: Vec<usize> = (0..ranges.len())
+        .filter(|&i| ranges[i].0 <= ranges[i].1)
+        .collect();
+    order.sort_unstable_by_key(|&i| ranges[i]);
+
+    let mut overlaps = Vec::new();
+    for (pos, &i) in order.iter().enumerate() {
+        let (start, end) = ranges[i];
+
+        for &j in &order[pos + 1..] {
+            let (other_start, other_end) = ranges[j];
+            if other_start > end {
+                break;
+            }
+
+            overlaps.push(RangeOverlap {
+                first: i.min(j),
+                second: i.max(j),
+                start: other_start.max(start),
+                end: other_end.min(end),
+            });
+        }
+    }
+
+    overlaps.sort_unstable_by_key(|overlap| (overlap.first, overlap.second));
+    overlaps
+}
+
+impl ProductRange {
+    #[inline]
+    pub fn bounds(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+}
+
+impl FromStr for ProductRange {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (label, body) = match s.split_once(':') {
+            Some((label, body)) => {
+                let label = label.trim();
+                if label.is_empty() {
+                    return Err("empty label");
+                }
+
+                (Some(label.to_owned()), body.trim())
+            }
+            None => (None, s.trim()),
+        };
+
+        if body.is_empty() {
+            return Err("missing range");
+        }
+
+        let parse_id = |id: &str| id.trim().parse::<usize>().map_err(|_| "invalid product ID");
+
+        let (start, end) = match body.split_once('-') {
+            Some((start, end)) if end.trim().is_empty() => {
+                let start = parse_id(start)?;
+                let band_end = 10usize
+                    .checked_pow(digit_count(start) as u32)
+                    .map_or(usize::MAX, |limit| limit - 1);
+
+                (start, band_end)
+            }
+            Some((start, end)) => (parse_id(start)?, parse_id(end)?),
+            None => {
+                let id = parse_id(body)?;
+                (id, id)
+            }
+        };
+
+        if start > end {
+            return Err("range starts after it ends");
+        }
+
+        Ok(Self { label, start, end })
+    }
+}
+
+impl fmt::Display for ProductRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(label) = &self.label {
+            write!(f, "{label}: ")?;
+        }
+
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl fmt::Display for RangeParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} in `{}`", self.line, self.reason, self.entry)
+    }
+}
+
+impl Error for RangeParseError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(label: Option<&str>, start: usize, end: usize) -> ProductRange {
+        ProductRange {
+            label: label.map(str::to_owned),
+            start,
+            end,
+        }
+    }
+
+    #[test]
+    fn test_parse_ranges_formats() {
+        let input = "\
+            # exported 2025-12-02
+            warehouse-a: 100-200
+            500-   # rest of the block
+            1234, 11-22,
+
+            cold storage: 7
+        ";
+
+        assert_eq!(
+            parse_ranges(input),
+            Ok(vec![
+                range(Some("warehouse-a"), 100, 200),
+                range(None, 500, 999),
+                range(None, 1234, 1234),
+                range(None, 11, 22),
+                range(Some("cold storage"), 7, 7),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_open_ended_ranges() {
+        let parse = |input: &str| parse_ranges(input).unwrap();
+
+        assert_eq!(parse("7-"), vec![range(None, 7, 9)]);
+        assert_eq!(parse("0-"), vec![range(None, 0, 9)]);
+        assert_eq!(parse("999 -"), vec![range(None, 999, 999)]);
+        assert_eq!(parse("1000-"), vec![range(None, 1000, 9999)]);
+        assert_eq!(
+            parse("9999999999999999999-"),
+            vec![range(
+                None,
+                9_999_999_999_999_999_999,
+                9_999_999_999_999_999_999
+            )]
+        );
+        assert_eq!(
+            parse("10000000000000000000-"),
+            vec![range(None, 10_000_000_000_000_000_000, usize::MAX)]
+        );
+    }
+
+    #[test]
+    fn test_parse_ranges_errors() {
+        let error = |input: &str| parse_ranges(input).unwrap_err();
+
+        assert_eq!(
+            error("1-2\n3-x"),
+            RangeParseError {
+                line: 2,
+                entry: "3-x".to_owned(),
+                reason: "invalid product ID"
+            }
+        );
+        assert_eq!(error("20-10").reason, "range starts after it ends");
+        assert_eq!(error(": 1-2").reason, "empty label");
+        assert_eq!(error("a:").reason, "missing range");
+        assert_eq!(error("-5").reason, "invalid product ID");
+        assert_eq!(error("1-2-3").reason, "invalid product ID");
+    }
+
+    #[test]
+    fn test_merge_ranges() {
+        let merged = merge_ranges([(40, 90), (10, 50), (95, 100), (91, 93), (7, 3), (95, 100)]);
+
+        assert_eq!(merged, vec![(10, 93), (95, 100)]);
+    }
+
+    #[test]
+    fn test_find_overlaps() {
+        let overlaps = find_overlaps(&[(10, 50), (40, 90), (51, 60), (95, 100), (95, 100)]);
+
+        assert_eq!(
+            overlaps,
+            vec![
+                RangeOverlap {
+                    first: 0,
+                    second: 1,
+                    start: 40,
+                    end: 50
+                },
+                RangeOverlap {
+                    first: 1,
+                    second: 2,
+                    start: 51,
+                    end: 60
+                },
+                RangeOverlap {
+                    first: 3,
+                    second: 4,
+                    start: 95,
+                    end: 100
+                },
+            ]
+        );
+    }
+}
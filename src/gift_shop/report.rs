@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::ops::AddAssign;
+
+use super::{InvalidProductIds, ProductRange, RepeatingPattern, repeating_patterns};
+
+/// Groups invalid ids by their shape.
+///
+/// An id is filed under every repetition count that reproduces it, so
+/// `111111` (period 1) shows up under 2, 3 and 6 repetitions. The classes
+/// with two repetitions add up to `identical_halves_sum`, while the classes
+/// where `repetitions * period == digits` hold every id exactly once and add
+/// up to `repeating_patterns_sum`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PatternClass {
+    pub digits: usize,
+    pub repetitions: usize,
+    pub period: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PatternTally {
+    pub count: usize,
+    pub sum: usize,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PatternBreakdown {
+    pub classes: BTreeMap<PatternClass, PatternTally>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RangeReport {
+    pub label: Option<String>,
+    pub start: usize,
+    pub end: usize,
+    pub totals: InvalidProductIds,
+    pub breakdown: PatternBreakdown,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct GiftShopReport {
+    pub ranges: Vec<RangeReport>,
+    pub totals: InvalidProductIds,
+    pub breakdown: PatternBreakdown,
+}
+
+#[must_use]
+pub fn build_report(ranges: &[ProductRange]) -> GiftShopReport {
+    let mut report = GiftShopReport::default();
+
+    for range in ranges {
+        let range = RangeReport::scan(range);
+
+        report.totals += range.totals;
+        report.breakdown += &range.breakdown;
+        report.ranges.push(range);
+    }
+
+    report
+}
+
+impl RangeReport {
+    pub fn scan(range: &ProductRange) -> Self {
+        let mut totals = InvalidProductIds::default();
+        let mut breakdown = PatternBreakdown::default();
+
+        for pattern in repeating_patterns(range.start, range.end) {
+            totals.record(&pattern);
+            breakdown.record(&pattern);
+        }
+
+        Self {
+            label: range.label.clone(),
+            start: range.start,
+            end: range.end,
+            totals,
+            breakdown,
+        }
+    }
+}
+
+impl PatternBreakdown {
+    pub fn record(&mut self, pattern: &RepeatingPattern) {
+        let digits = pattern.digits();
+        let period = pattern.period();
+
+        for repetitions in 2..=pattern.repetitions {
+            if !pattern.repetitions.is_multiple_of(repetitions) {
+                continue;
+            }
+
+            let tally = self
+                .classes
+                .entry(PatternClass {
+                    digits,
+                    repetitions,
+                    period,
+                })
+                .or_default();
+
+            tally.count += 1;
+            tally.sum += pattern.id;
+        }
+    }
+
+    /// Combined tally of every class with the given repetition count.
+    pub fn by_repetitions(&self, repetitions: usize) -> PatternTally {
+        self.classes
+            .iter()
+            .filter(|(class, _)| class.repetitions == repetitions)
+            .fold(PatternTally::default(), |mut acc, (_, tally)| {
+                acc += *tally;
+                acc
+            })
+    }
+
+    /// Combined tally of every class with the given digit length, counting
+    /// each id once.
+    pub fn by_digits(&self, digits: usize) -> PatternTally {
+        self.classes
+            .iter()
+            .filter(|(class, _)| class.digits == digits && class.is_primitive())
+            .fold(PatternTally::default(), |mut acc, (_, tally)| {
+                acc += *tally;
+                acc
+            })
+    }
+}
+
+impl PatternClass {
+    /// Whether this class describes ids by their shortest chunk.
+    #[inline]
+    pub fn is_primitive(&self) -> bool {
+        self.repetitions * self.period == self.digits
+    }
+}
+
+impl AddAssign for PatternTally {
+    fn add_assign(&mut self, rhs: Self) {
+        self.count += rhs.count;
+        self.sum += rhs.sum;
+    }
+}
+
+impl AddAssign<&PatternBreakdown> for PatternBreakdown {
+    fn add_assign(&mut self, rhs: &PatternBreakdown) {
+        for (class, tally) in &rhs.classes {
+            *self.classes.entry(*class).or_default() += *tally;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gift_shop::{INPUT, parse_ranges, run_simulation};
+
+    const EXAMPLE: &str = "11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
+
+    #[test]
+    fn test_report_totals_match_simulation() {
+        for input in [EXAMPLE, INPUT] {
+            let report = build_report(&parse_ranges(input).unwrap());
+
+            assert_eq!(report.totals, run_simulation(input).unwrap());
+            assert_eq!(
+                report.breakdown.by_repetitions(2).sum,
+                report.totals.identical_halves_sum
+            );
+
+            let primitive_sum: usize = report
+                .breakdown
+                .classes
+                .iter()
+                .filter(|(class, _)| class.is_primitive())
+                .map(|(_, tally)| tally.sum)
+                .sum();
+            assert_eq!(primitive_sum, report.totals.repeating_patterns_sum);
+        }
+    }
+
+    #[test]
+    fn test_report_range_subtotals() {
+        let report = build_report(&parse_ranges(EXAMPLE).unwrap());
+
+        assert_eq!(report.ranges.len(), 11);
+        assert_eq!((report.ranges[1].start, report.ranges[1].end), (95, 115));
+        assert_eq!(report.ranges[1].totals.identical_halves_sum, 99);
+        assert_eq!(report.ranges[1].totals.repeating_patterns_sum, 99 + 111);
+    }
+
+    #[test]
+    fn test_report_keeps_labels() {
+        let ranges = parse_ranges("front: 11-22\nback: 95-115").unwrap();
+        let report = build_report(&ranges);
+
+        assert_eq!(report.ranges[0].label.as_deref(), Some("front"));
+        assert_eq!(report.ranges[1].label.as_deref(), Some("back"));
+        assert_eq!(report.ranges[1].totals.identical_halves_sum, 99);
+    }
+
+    #[test]
+    fn test_breakdown_files_id_under_every_repetition_count() {
+        let report = RangeReport::scan(&"111111".parse().unwrap());
+        let classes: Vec<_> = report
+            .breakdown
+            .classes
+            .keys()
+            .map(|c| (c.digits, c.repetitions, c.period))
+            .collect();
+
+        assert_eq!(classes, vec![(6, 2, 1), (6, 3, 1), (6, 6, 1)]);
+        assert_eq!(
+            report.breakdown.by_digits(6),
+            PatternTally {
+                count: 1,
+                sum: 111_111
+            }
+        );
+    }
+}
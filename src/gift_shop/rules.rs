@@ -0,0 +1,494 @@
+use std::iter::{self, Peekable};
+use std::str::FromStr;
+
+use super::{RepeatingPattern, digit_count, repeating_patterns};
+
+/// A property that flags product IDs as invalid.
+pub trait IdRule {
+    fn name(&self) -> &str;
+
+    /// Every matching id in `start..=end`, in ascending order and without
+    /// duplicates.
+    fn matches(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = usize> + '_>;
+}
+
+/// Ids made of the same chunk written twice, e.g. `123123`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct IdenticalHalves;
+
+/// Ids made of the same chunk written at least twice, e.g. `121212`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct RepeatedChunk;
+
+/// Ids of at least two digits that read the same in both directions.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Palindrome;
+
+/// Ids of at least two digits that are all equal, e.g. `7777`.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct UniformDigits;
+
+/// A fixed-width digit template.
+///
+/// Digits match themselves, `?` matches any digit, and lowercase letters
+/// stand for a digit that must be the same everywhere the letter appears, so
+/// `4??4` matches `4004` through `4994` and `ab?ba` matches five-digit
+/// palindromes.
+#[derive(Debug, Clone)]
+pub struct DigitTemplate {
+    source: String,
+    shape: DigitShape,
+}
+
+/// Combines several rules, reporting each matching id once.
+pub struct RuleSet {
+    rules: Vec<Box<dyn IdRule>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleMatch {
+    pub id: usize,
+    /// Indices into [`RuleSet::rules`] of every rule that flagged the id.
+    pub rules: Vec<usize>,
+}
+
+pub struct RuleMatches<'a> {
+    sources: Vec<Peekable<Box<dyn Iterator<Item = usize> + 'a>>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Slot {
+    Fixed(u8),
+    Free,
+    SameAs(usize),
+}
+
+/// Positional constraints for ids of exactly `slots.len()` digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DigitShape {
+    slots: Vec<Slot>,
+}
+
+impl IdRule for IdenticalHalves {
+    fn name(&self) -> &str {
+        "identical-halves"
+    }
+
+    fn matches(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(
+            repeating_patterns(start, end)
+                .filter(RepeatingPattern::has_identical_halves)
+                .map(|pattern| pattern.id),
+        )
+    }
+}
+
+impl IdRule for RepeatedChunk {
+    fn name(&self) -> &str {
+        "repeating-pattern"
+    }
+
+    fn matches(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(repeating_patterns(start, end).map(|pattern| pattern.id))
+    }
+}
+
+impl IdRule for Palindrome {
+    fn name(&self) -> &str {
+        "palindrome"
+    }
+
+    fn matches(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(shaped_matches(start.max(10), end, DigitShape::palindrome))
+    }
+}
+
+impl IdRule for UniformDigits {
+    fn name(&self) -> &str {
+        "uniform-digits"
+    }
+
+    fn matches(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(shaped_matches(start.max(10), end, DigitShape::uniform))
+    }
+}
+
+impl IdRule for DigitTemplate {
+    fn name(&self) -> &str {
+        &self.source
+    }
+
+    fn matches(&self, start: usize, end: usize) -> Box<dyn Iterator<Item = usize> + '_> {
+        Box::new(self.shape.clone().into_matches(start, end))
+    }
+}
+
+impl FromStr for DigitTemplate {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let source = s.trim();
+        if source.is_empty() {
+            return Err("empty digit template");
+        }
+
+        // usize ids never have more than 20 digits.
+        if source.len() > 20 {
+            return Err("digit template is longer than 20 digits");
+        }
+
+        let mut variables: [Option<usize>; 26] = [None; 26];
+        let mut slots = Vec::with_capacity(source.len());
+
+        for (pos, c) in source.chars().enumerate() {
+            let slot = match c {
+                '0'..='9' => Slot::Fixed(c as u8 - b'0'),
+                '?' => Slot::Free,
+                'a'..='z' => {
+                    let var = &mut variables[(c as u8 - b'a') as usize];
+                    match *var {
+                        Some(first) => Slot::SameAs(first),
+                        None => {
+                            *var = Some(pos);
+                            Slot::Free
+                        }
+                    }
+                }
+                _ => {
+                    return Err(
+                        "digit templates may only contain digits, `?` and lowercase letters",
+                    );
+                }
+            };
+
+            slots.push(slot);
+        }
+
+        if slots.len() > 1 && slots[0] == Slot::Fixed(0) {
+            return Err("digit template cannot start with 0");
+        }
+
+        Ok(Self {
+            source: source.to_owned(),
+            shape: DigitShape { slots },
+        })
+    }
+}
+
+impl RuleSet {
+    pub fn new(rules: Vec<Box<dyn IdRule>>) -> Self {
+        Self { rules }
+    }
+
+    /// Position of [`IdenticalHalves`] in [`Self::builtin`].
+    pub const IDENTICAL_HALVES: usize = 0;
+    /// Position of [`RepeatedChunk`] in [`Self::builtin`].
+    pub const REPEATED_CHUNK: usize = 1;
+
+    /// The identical-halves and repeating-pattern rules used by the puzzle,
+    /// at [`Self::IDENTICAL_HALVES`] and [`Self::REPEATED_CHUNK`].
+    pub fn builtin() -> Self {
+        Self::new(vec![Box::new(IdenticalHalves), Box::new(RepeatedChunk)])
+    }
+
+    pub fn rules(&self) -> &[Box<dyn IdRule>] {
+        &self.rules
+    }
+
+    pub fn matches(&self, start: usize, end: usize) -> RuleMatches<'_> {
+        RuleMatches {
+            sources: self
+                .rules
+                .iter()
+                .map(|rule| rule.matches(start, end).peekable())
+                .collect(),
+        }
+    }
+}
+
+impl Iterator for RuleMatches<'_> {
+    type Item = RuleMatch;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let id = self
+            .sources
+            .iter_mut()
+            .filter_map(|source| source.peek().copied())
+            .min()?;
+
+        let mut rules = Vec::new();
+        for (i, source) in self.sources.iter_mut().enumerate() {
+            if source.next_if_eq(&id).is_some() {
+                rules.push(i);
+            }
+        }
+
+        Some(RuleMatch { id, rules })
+    }
+}
+
+fn shaped_matches(
+    start: usize,
+    end: usize,
+    shape_for: fn(usize) -> DigitShape,
+) -> impl Iterator<Item = usize> {
+    // a reversed range still yields nothing: every shape's first match at or
+    // above `start` is already past `end`.
+    let lengths = digit_count(start)..=digit_count(end);
+
+    lengths.flat_map(move |len| shape_for(len).into_matches(start, end))
+}
+
+impl DigitShape {
+    fn palindrome(len: usize) -> Self {
+        let slots = (0..len)
+            .map(|pos| {
+                if pos < len.div_ceil(2) {
+                    Slot::Free
+                } else {
+                    Slot::SameAs(len - 1 - pos)
+                }
+            })
+            .collect();
+
+        Self { slots }
+    }
+
+    fn uniform(len: usize) -> Self {
+        let slots = (0..len)
+            .map(|pos| {
+                if pos == 0 {
+                    Slot::Free
+                } else {
+                    Slot::SameAs(0)
+                }
+            })
+            .collect();
+
+        Self { slots }
+    }
+
+    fn into_matches(self, start: usize, end: usize) -> impl Iterator<Item = usize> {
+        iter::successors(self.next_at_least(start), move |&id| {
+            self.next_at_least(id.checked_add(1)?)
+        })
+        .take_while(move |&id| id <= end)
+    }
+
+    /// Smallest id of this shape that is at least `n`.
+    fn next_at_least(&self, n: usize) -> Option<usize> {
+        let len = self.slots.len();
+        let lowest = if len == 1 {
+            0
+        } else {
+            10u128.pow(len as u32 - 1)
+        };
+        let highest = 10u128.pow(len as u32) - 1;
+
+        let n = (n as u128).max(lowest);
+        if n > highest {
+            return None;
+        }
+
+        let target = to_digits(n, len);
+        let mut digits = vec![0; len];
+
+        let mut matched = 0;
+        while matched < len && self.allows(matched, target[matched], &digits) {
+            digits[matched] = target[matched];
+            matched += 1;
+        }
+
+        if matched == len {
+            return Some(n as usize);
+        }
+
+        // keep as much of `n` as possible and bump the deepest digit that can
+        // still be raised; everything after it takes its smallest value.
+        for pos in (0..=matched).rev() {
+            for d in target[pos] + 1..=9 {
+                if self.allows(pos, d, &digits) {
+                    digits[pos] = d;
+                    self.fill_smallest(&mut digits, pos + 1);
+
+                    let id = digits.iter().fold(0u128, |acc, &d| acc * 10 + d as u128);
+                    return usize::try_from(id).ok();
+                }
+            }
+        }
+
+        None
+    }
+
+    #[inline]
+    fn allows(&self, pos: usize, digit: u8, digits: &[u8]) -> bool {
+        if pos == 0 && digit == 0 && self.slots.len() > 1 {
+            return false;
+        }
+
+        match self.slots[pos] {
+            Slot::Fixed(d) => d == digit,
+            Slot::Free => true,
+            Slot::SameAs(other) => digits[other] == digit,
+        }
+    }
+
+    fn fill_smallest(&self, digits: &mut [u8], from: usize) {
+        for pos in from..digits.len() {
+            digits[pos] = match self.slots[pos] {
+                Slot::Fixed(d) => d,
+                Slot::Free => 0,
+                Slot::SameAs(other) => digits[other],
+            };
+        }
+    }
+}
+
+fn to_digits(mut n: u128, len: usize) -> Vec<u8> {
+    let mut digits = vec![0; len];
+    for digit in digits.iter_mut().rev() {
+        *digit = (n % 10) as u8;
+        n /= 10;
+    }
+
+    digits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every id in `start..=end` whose decimal digits satisfy `check`.
+    fn brute_force(start: usize, end: usize, check: impl Fn(&[u8]) -> bool) -> Vec<usize> {
+        (start..=end)
+            .filter(|id| check(id.to_string().as_bytes()))
+            .collect()
+    }
+
+    fn identical_halves(s: &[u8]) -> bool {
+        s.len().is_multiple_of(2) && s[..s.len() / 2] == s[s.len() / 2..]
+    }
+
+    fn repeated_chunk(s: &[u8]) -> bool {
+        (1..s.len())
+            .any(|chunk| s.len().is_multiple_of(chunk) && s[..chunk].repeat(s.len() / chunk) == s)
+    }
+
+    fn palindrome(s: &[u8]) -> bool {
+        s.len() >= 2 && s.iter().eq(s.iter().rev())
+    }
+
+    /// Matches `s` against a digit template the slow way, remembering the
+    /// digit each letter stands for.
+    fn fits_template(template: &str, s: &[u8]) -> bool {
+        let mut letters = [None; 26];
+
+        template.len() == s.len()
+            && template.bytes().zip(s).all(|(t, &d)| match t {
+                b'?' => true,
+                b'a'..=b'z' => *letters[(t - b'a') as usize].get_or_insert(d) == d,
+                _ => t == d,
+            })
+    }
+
+    #[test]
+    fn test_builtin_rules_match_checks() {
+        let checks: [fn(&[u8]) -> bool; 2] = [identical_halves, repeated_chunk];
+
+        for (rule, check) in RuleSet::builtin().rules().iter().zip(checks) {
+            let ids: Vec<_> = rule.matches(0, 150_000).collect();
+
+            assert_eq!(ids, brute_force(0, 150_000, check), "{}", rule.name());
+        }
+
+        let builtin = RuleSet::builtin();
+        assert_eq!(
+            builtin.rules()[RuleSet::IDENTICAL_HALVES].name(),
+            IdenticalHalves.name()
+        );
+        assert_eq!(
+            builtin.rules()[RuleSet::REPEATED_CHUNK].name(),
+            RepeatedChunk.name()
+        );
+    }
+
+    #[test]
+    fn test_palindrome() {
+        let ids: Vec<_> = Palindrome.matches(90, 131).collect();
+
+        assert_eq!(ids, vec![99, 101, 111, 121, 131]);
+        assert_eq!(Palindrome.matches(1_234_321, 1_234_321).count(), 1);
+        assert_eq!(Palindrome.matches(0, 9).count(), 0);
+        assert_eq!(
+            Palindrome.matches(0, 100_000).collect::<Vec<_>>(),
+            brute_force(0, 100_000, palindrome)
+        );
+    }
+
+    #[test]
+    fn test_uniform_digits() {
+        let ids: Vec<_> = UniformDigits.matches(0, 1000).collect();
+
+        assert_eq!(
+            ids,
+            vec![
+                11, 22, 33, 44, 55, 66, 77, 88, 99, 111, 222, 333, 444, 555, 666, 777, 888, 999
+            ]
+        );
+        assert_eq!(
+            UniformDigits.matches(99_999_999_990, usize::MAX).next(),
+            Some(99_999_999_999)
+        );
+    }
+
+    #[test]
+    fn test_digit_template() {
+        let template: DigitTemplate = "4?a?a".parse().unwrap();
+
+        assert_eq!(
+            template.matches(41_323, 41_324).collect::<Vec<_>>(),
+            vec![41_323]
+        );
+        assert_eq!(
+            template.matches(0, 100_000).collect::<Vec<_>>(),
+            brute_force(0, 100_000, |s| fits_template("4?a?a", s))
+        );
+        assert_eq!(template.matches(41_324, 41_999).next(), Some(41_333));
+    }
+
+    #[test]
+    fn test_digit_template_errors() {
+        assert!("".parse::<DigitTemplate>().is_err());
+        assert!("0??".parse::<DigitTemplate>().is_err());
+        assert!("1x#".parse::<DigitTemplate>().is_err());
+        assert!("?".repeat(21).parse::<DigitTemplate>().is_err());
+    }
+
+    #[test]
+    fn test_rule_set_deduplicates() {
+        let set = RuleSet::new(vec![
+            Box::new(IdenticalHalves),
+            Box::new(RepeatedChunk),
+            Box::new(Palindrome),
+        ]);
+        let matches: Vec<_> = set.matches(1000, 1111).collect();
+
+        assert_eq!(
+            matches,
+            vec![
+                RuleMatch {
+                    id: 1001,
+                    rules: vec![2]
+                },
+                RuleMatch {
+                    id: 1010,
+                    rules: vec![0, 1]
+                },
+                RuleMatch {
+                    id: 1111,
+                    rules: vec![0, 1, 2]
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,391 @@
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+
+use super::{
+    BatteryBank, Joltage, Selection, cmp_numbers, cmp_spelled, keep_better, prepend, spell,
+};
+
+/// Wiring restrictions on which batteries a selection may use.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SelectionConstraints {
+    /// Minimum number of unused batteries between two chosen ones.
+    pub min_gap: usize,
+    /// Positions that must not be chosen, e.g. damaged cells.
+    pub excluded: BTreeSet<usize>,
+    /// Maximum number of chosen batteries sitting next to each other.
+    pub max_run: Option<usize>,
+}
+
+/// How taking a battery moves the search on, under a minimum gap and an
+/// optional cap on runs of adjacent chosen batteries.
+#[derive(Debug, Clone, Copy)]
+struct Runs {
+    max: Option<usize>,
+    gap: usize,
+    /// Batteries in the bank; the search never moves past the end.
+    len: usize,
+}
+
+impl Runs {
+    /// Run lengths worth telling apart: just 0 when there is no cap.
+    fn width(self) -> usize {
+        self.max.map_or(1, |max| max + 1)
+    }
+
+    /// Whether a battery may extend a run of `run` chosen ones.
+    fn allows(self, run: usize) -> bool {
+        self.max.is_none_or(|max| run < max)
+    }
+
+    /// Position and run the search continues from after taking battery `i`
+    /// within a run of `run`.
+    fn next(self, i: usize, run: usize) -> (usize, usize) {
+        if self.gap > 0 {
+            ((i + 1).saturating_add(self.gap).min(self.len), 0)
+        } else if self.max.is_some() {
+            (i + 1, run + 1)
+        } else {
+            (i + 1, 0)
+        }
+    }
+}
+
+impl BatteryBank {
+    /// The largest joltage made of exactly `k` batteries that respects
+    /// `constraints`, or `None` when no such selection exists.
+    pub fn select_constrained(
+        &self,
+        k: usize,
+        constraints: &SelectionConstraints,
+    ) -> Option<Selection> {
+        let values = self.joltages();
+        let n = values.len();
+        let max_run = constraints.max_run.map(|max| max.min(k));
+        let top = values.iter().copied().max().unwrap_or(0);
+
+        if k == 0 {
+            return Some(Selection {
+                indices: Vec::new(),
+                joltage: Joltage::default(),
+            });
+        }
+
+        if max_run == Some(0) {
+            return None;
+        }
+
+        // capacity[i][r]: most batteries still selectable from position `i`
+        // onwards when picking `i` itself would extend a run of `r` chosen
+        // batteries. Runs are only tracked under a limit; without one `r`
+        // stays 0. The row for the end of the bank stays at zero.
+        let runs = Runs {
+            max: max_run,
+            gap: constraints.min_gap,
+            len: n,
+        };
+        let width = runs.width();
+        let mut capacity = vec![0usize; (n + 1) * width];
+        let cap = |capacity: &[usize], i: usize, r: usize| capacity[i * width + r];
+
+        for i in (0..n).rev() {
+            for r in 0..width {
+                let skip = cap(&capacity, i + 1, 0);
+                let take = if constraints.excluded.contains(&i) || !runs.allows(r) {
+                    0
+                } else {
+                    let (ni, nr) = runs.next(i, r);
+                    1 + cap(&capacity, ni, nr)
+                };
+
+                capacity[i * width + r] = skip.max(take);
+            }
+        }
+
+        if cap(&capacity, 0, 0) < k {
+            return None;
+        }
+
+        if !self.is_single_digit() {
+            return Some(concat_constrained(&values, k, constraints, runs));
+        }
+
+        // greedily take the largest digit that still leaves room for the
+        // rest. Among equal digits the earliest position wins: it leaves every
+        // option of a later one open, and a shorter run at worst.
+        let mut indices = Vec::with_capacity(k);
+        let (mut start, mut run) = (0, 0);
+
+        for picked in 0..k {
+            let needed = k - picked;
+            let mut best: Option<(usize, usize, usize)> = None;
+
+            for j in start..n {
+                if cap(&capacity, j, 0) < needed && j > start {
+                    break;
+                }
+
+                if constraints.excluded.contains(&j) {
+                    continue;
+                }
+
+                let run_before = if j == start { run } else { 0 };
+                if !runs.allows(run_before) {
+                    continue;
+                }
+
+                let (next, run_after) = runs.next(j, run_before);
+                if 1 + cap(&capacity, next, run_after) < needed {
+                    continue;
+                }
+
+                if best.is_none_or(|(b, _, _)| values[j] > values[b]) {
+                    best = Some((j, next, run_after));
+                    if values[j] == top {
+                        break;
+                    }
+                }
+            }
+
+            let (j, next, run_after) = best?;
+            indices.push(j);
+
+            (start, run) = (next, run_after);
+        }
+
+        let chosen: Vec<u32> = indices.iter().map(|&i| values[i]).collect();
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+}
+
+/// [`BatteryBank::select_constrained`] for a bank that is not
+/// [single-digit](BatteryBank::is_single_digit), once a selection is known
+/// to exist. Like the unconstrained case, this works out from the back the
+/// best string every suffix spells, here for each run length and number of
+/// batteries still to pick.
+fn concat_constrained(
+    values: &[u32],
+    k: usize,
+    constraints: &SelectionConstraints,
+    runs: Runs,
+) -> Selection {
+    let n = values.len();
+    let digits = spell(values);
+
+    // best[i][r][j]: largest string `j` batteries from position `i` onwards
+    // spell after a nonzero digit when picking `i` would extend a run of `r`;
+    // leading[i][r][j]: the same at the front of the selection, where leading
+    // zeros do not count. took_*: whether that string starts with battery `i`.
+    let width = runs.width();
+    let at = |i: usize, r: usize, j: usize| (i * width + r) * (k + 1) + j;
+    let mut best: Vec<Option<Vec<u8>>> = vec![None; (n + 1) * width * (k + 1)];
+    for i in 0..=n {
+        for r in 0..width {
+            best[at(i, r, 0)] = Some(Vec::new());
+        }
+    }
+    let mut leading = best.clone();
+    let mut took_best = vec![false; best.len()];
+    let mut took_leading = took_best.clone();
+
+    for i in (0..n).rev() {
+        for r in 0..width {
+            let allowed = !constraints.excluded.contains(&i) && runs.allows(r);
+            let (ni, nr) = runs.next(i, r);
+
+            for j in 1..=k {
+                let skip = at(i + 1, 0, j);
+                let (take, take_leading) = if allowed {
+                    let take = prepend(&digits[i], best[at(ni, nr, j - 1)].as_ref());
+                    let take_leading = if values[i] == 0 {
+                        prepend(&digits[i], leading[at(ni, nr, j - 1)].as_ref())
+                    } else {
+                        take.clone()
+                    };
+
+                    (take, take_leading)
+                } else {
+                    (None, None)
+                };
+
+                // taking wins ties, so the earliest battery is the one used.
+                best[at(i, r, j)] = best[skip].clone();
+                took_best[at(i, r, j)] = keep_better(
+                    &mut best[at(i, r, j)],
+                    take,
+                    |a, b| cmp_spelled(a, b),
+                    Ordering::Greater,
+                );
+                leading[at(i, r, j)] = leading[skip].clone();
+                took_leading[at(i, r, j)] = keep_better(
+                    &mut leading[at(i, r, j)],
+                    take_leading,
+                    |a, b| cmp_numbers(a, b),
+                    Ordering::Greater,
+                );
+            }
+        }
+    }
+
+    let mut indices = Vec::with_capacity(k);
+    let (mut i, mut r) = (0, 0);
+    let mut at_front = true;
+    while indices.len() < k {
+        let took = if at_front { &took_leading } else { &took_best };
+
+        if took[at(i, r, k - indices.len())] {
+            indices.push(i);
+            at_front &= values[i] == 0;
+            (i, r) = runs.next(i, r);
+        } else {
+            (i, r) = (i + 1, 0);
+        }
+    }
+
+    let chosen: Vec<u32> = indices.iter().map(|&i| values[i]).collect();
+
+    Selection {
+        indices,
+        joltage: Joltage::concat(&chosen),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::XorShift;
+
+    /// Whether `indices` (ascending) respects every constraint.
+    fn allows(constraints: &SelectionConstraints, indices: &[usize]) -> bool {
+        if indices.iter().any(|i| constraints.excluded.contains(i)) {
+            return false;
+        }
+
+        let mut run = 0;
+        for (pos, &i) in indices.iter().enumerate() {
+            let adjacent = pos > 0 && indices[pos - 1] + 1 == i;
+            if pos > 0 && i - indices[pos - 1] - 1 < constraints.min_gap {
+                return false;
+            }
+
+            run = if adjacent { run + 1 } else { 1 };
+            if constraints.max_run.is_some_and(|max| run > max) {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    fn brute_force(
+        bank: &BatteryBank,
+        k: usize,
+        constraints: &SelectionConstraints,
+    ) -> Option<Joltage> {
+        let n = bank.batteries.len();
+
+        (0u32..1 << n)
+            .filter(|mask| mask.count_ones() as usize == k)
+            .map(|mask| (0..n).filter(|i| mask & (1 << i) != 0).collect::<Vec<_>>())
+            .filter(|indices| allows(constraints, indices))
+            .map(|indices| {
+                let chosen: Vec<u32> = indices.iter().map(|&i| bank.batteries[i].joltage).collect();
+                Joltage::concat(&chosen)
+            })
+            .max()
+    }
+
+    #[test]
+    fn test_unconstrained_matches_select() {
+        let bank: BatteryBank = "818181911112111".parse().unwrap();
+
+        for k in 0..=16 {
+            let constrained = bank.select_constrained(k, &SelectionConstraints::default());
+
+            assert_eq!(constrained, bank.select(k), "k = {k}");
+        }
+    }
+
+    #[test]
+    fn test_constrained_example() {
+        let bank: BatteryBank = "811111111111119".parse().unwrap();
+        let constraints = SelectionConstraints {
+            min_gap: 1,
+            excluded: BTreeSet::from([0]),
+            max_run: None,
+        };
+
+        let selection = bank.select_constrained(3, &constraints).unwrap();
+
+        assert_eq!(selection.indices, vec![1, 3, 14]);
+        assert_eq!(selection.joltage, Joltage::from(119usize));
+        assert_eq!(bank.select_constrained(8, &constraints), None);
+    }
+
+    #[test]
+    fn test_constrained_multi_digit() {
+        let bank: BatteryBank = "9,12,5,4".parse().unwrap();
+        let constraints = SelectionConstraints {
+            max_run: Some(1),
+            ..SelectionConstraints::default()
+        };
+
+        let selection = bank.select_constrained(2, &constraints).unwrap();
+
+        assert_eq!(selection.indices, vec![1, 3]);
+        assert_eq!(selection.joltage, Joltage::from(124usize));
+    }
+
+    #[test]
+    fn test_gap_past_the_bank() {
+        for bank in ["3951", "3,12,40,7"] {
+            let bank: BatteryBank = bank.parse().unwrap();
+
+            for min_gap in [4, 100_000_000_000, usize::MAX] {
+                let constraints = SelectionConstraints {
+                    min_gap,
+                    ..SelectionConstraints::default()
+                };
+
+                assert_eq!(bank.select_constrained(1, &constraints), bank.select(1));
+                assert_eq!(bank.select_constrained(2, &constraints), None);
+            }
+        }
+    }
+
+    #[test]
+    fn test_constrained_against_brute_force() {
+        let mut rng = XorShift(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..1500 {
+            let n = 1 + rng.next(11);
+            let bank = rng.bank(n, &[0, 3, 7, 9, 12, 40]);
+
+            let constraints = SelectionConstraints {
+                min_gap: rng.pick(&[0, 0, 1, 2, usize::MAX]),
+                excluded: (0..n).filter(|_| rng.next(5) == 0).collect(),
+                max_run: rng.pick(&[None, Some(1), Some(2), Some(3)]),
+            };
+            let k = rng.next(n + 1);
+
+            let selection = bank.select_constrained(k, &constraints);
+            let expected = brute_force(&bank, k, &constraints);
+
+            match (&selection, &expected) {
+                (Some(selection), Some(expected)) => {
+                    assert!(
+                        allows(&constraints, &selection.indices),
+                        "{bank:?} {constraints:?}"
+                    );
+                    assert_eq!(selection.indices.len(), k);
+                    assert_eq!(&selection.joltage, expected);
+                }
+                (None, None) => {}
+                _ => panic!("{bank:?} k={k} {constraints:?}: {selection:?} vs {expected:?}"),
+            }
+        }
+    }
+}
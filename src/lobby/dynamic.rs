@@ -0,0 +1,450 @@
+use std::str::FromStr;
+
+use super::{Battery, BatteryBank, Joltage, Selection};
+
+/// A battery bank that supports swapping, adding and removing cells in the
+/// field.
+///
+/// Cells are kept in about √n blocks of about √n cells that remember their
+/// strongest battery, so an update only touches one block and a max-`k`
+/// query walks block summaries instead of every cell. The blocks are cut
+/// again whenever the bank grows or shrinks too far from the size they were
+/// cut for, which keeps updates at amortized O(√n) and queries at O(k·√n).
+/// Banks that are not [single-digit](BatteryBank::is_single_digit) answer
+/// queries with a full pass instead.
+#[derive(Debug, Clone)]
+pub struct DynamicBatteryBank {
+    blocks: Vec<Block>,
+    len: usize,
+    block_size: usize,
+}
+
+/// One change to a [`DynamicBatteryBank`], written `set:<i>:<joltage>`,
+/// `insert:<i>:<joltage>` or `remove:<i>` with 0-based positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BankEdit {
+    Replace { index: usize, joltage: u32 },
+    Insert { index: usize, joltage: u32 },
+    Remove { index: usize },
+}
+
+#[derive(Debug, Clone)]
+struct Block {
+    batteries: Vec<Battery>,
+    max: u32,
+}
+
+impl From<BatteryBank> for DynamicBatteryBank {
+    fn from(bank: BatteryBank) -> Self {
+        let block_size = block_size_for(bank.batteries.len());
+        Self::with_block_size(bank, block_size)
+    }
+}
+
+/// About √len, and never 0.
+fn block_size_for(len: usize) -> usize {
+    len.isqrt().max(1)
+}
+
+impl FromStr for BankEdit {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.trim().split(':');
+        let (Some(op), Some(index)) = (parts.next(), parts.next()) else {
+            return Err("edit needs an operation and a position");
+        };
+
+        let index = index.parse().map_err(|_| "invalid position")?;
+        let joltage = parts
+            .next()
+            .map(|joltage| joltage.parse().map_err(|_| "invalid joltage"))
+            .transpose()?;
+
+        if parts.next().is_some() {
+            return Err("too many fields in edit");
+        }
+
+        match (op, joltage) {
+            ("set", Some(joltage)) => Ok(Self::Replace { index, joltage }),
+            ("insert", Some(joltage)) => Ok(Self::Insert { index, joltage }),
+            ("remove", None) => Ok(Self::Remove { index }),
+            ("set" | "insert", None) => Err("edit needs a joltage"),
+            ("remove", Some(_)) => Err("remove takes no joltage"),
+            _ => Err("unknown edit"),
+        }
+    }
+}
+
+impl DynamicBatteryBank {
+    fn with_block_size(bank: BatteryBank, block_size: usize) -> Self {
+        let blocks = bank
+            .batteries
+            .chunks(block_size)
+            .map(|chunk| Block::new(chunk.to_vec()))
+            .collect();
+
+        Self {
+            blocks,
+            len: bank.batteries.len(),
+            block_size,
+        }
+    }
+
+    /// Cuts the blocks again once the bank has drifted from the size they
+    /// were cut for, or removals have left too many small ones behind. Each
+    /// rebuild is O(n) and follows at least √n updates.
+    fn rebalance(&mut self) {
+        let size = self.block_size;
+        let outgrown = self.len > 4 * size * size;
+        let shrunk = size > 1 && 4 * self.len < size * size;
+        let fragmented = self.blocks.len() > 2 * (self.len / size + 1);
+
+        if outgrown || shrunk || fragmented {
+            *self = Self::from(self.to_bank());
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn get(&self, index: usize) -> Option<Battery> {
+        if index >= self.len {
+            return None;
+        }
+
+        let (block, offset) = self.locate(index);
+        Some(self.blocks[block].batteries[offset])
+    }
+
+    /// Swaps the battery at `index` for one with the given joltage,
+    /// returning the old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replace(&mut self, index: usize, joltage: u32) -> Battery {
+        assert!(index < self.len, "battery index {index} out of bounds");
+
+        let (block, offset) = self.locate(index);
+        let block = &mut self.blocks[block];
+        let old = std::mem::replace(&mut block.batteries[offset], Battery { joltage });
+        block.refresh_max();
+
+        old
+    }
+
+    /// Inserts a battery at `index`, shifting every later battery right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, joltage: u32) {
+        assert!(index <= self.len, "battery index {index} out of bounds");
+
+        if self.blocks.is_empty() {
+            self.blocks.push(Block::new(Vec::new()));
+        }
+
+        let (block, offset) = if index == self.len {
+            let last = self.blocks.len() - 1;
+            (last, self.blocks[last].batteries.len())
+        } else {
+            self.locate(index)
+        };
+
+        let target = &mut self.blocks[block];
+        target.batteries.insert(offset, Battery { joltage });
+        target.max = target.max.max(joltage);
+        self.len += 1;
+
+        if target.batteries.len() > 2 * self.block_size {
+            let tail = target.batteries.split_off(self.block_size);
+            target.refresh_max();
+            self.blocks.insert(block + 1, Block::new(tail));
+        }
+
+        self.rebalance();
+    }
+
+    /// Removes and returns the battery at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Battery {
+        assert!(index < self.len, "battery index {index} out of bounds");
+
+        let (block, offset) = self.locate(index);
+        let target = &mut self.blocks[block];
+        let removed = target.batteries.remove(offset);
+        self.len -= 1;
+
+        if target.batteries.is_empty() {
+            self.blocks.remove(block);
+        } else if removed.joltage == target.max {
+            target.refresh_max();
+        }
+
+        self.rebalance();
+        removed
+    }
+
+    /// Applies `edit`, or explains why its position is out of bounds.
+    pub fn apply(&mut self, edit: BankEdit) -> Result<(), &'static str> {
+        match edit {
+            BankEdit::Replace { index, joltage } if index < self.len => {
+                self.replace(index, joltage);
+            }
+            BankEdit::Insert { index, joltage } if index <= self.len => {
+                self.insert(index, joltage);
+            }
+            BankEdit::Remove { index } if index < self.len => {
+                self.remove(index);
+            }
+            _ => return Err("edit position out of bounds"),
+        }
+
+        Ok(())
+    }
+
+    /// Same as [`BatteryBank::select`] on the current cells.
+    pub fn select(&self, k: usize) -> Option<Selection> {
+        if k > self.len {
+            return None;
+        }
+
+        // the block walk needs a single-digit bank, see
+        // `BatteryBank::is_single_digit`.
+        let top = self.blocks.iter().map(|block| block.max).max().unwrap_or(0);
+        if top > 9 {
+            return self.to_bank().select(k);
+        }
+
+        let mut indices = Vec::with_capacity(k);
+        let mut chosen = Vec::with_capacity(k);
+        let mut start = 0;
+
+        for remaining in (0..k).rev() {
+            let (pick, joltage) = self.max_position(start, self.len - 1 - remaining, top);
+
+            indices.push(pick);
+            chosen.push(joltage);
+            start = pick + 1;
+        }
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+
+    /// Same as [`BatteryBank::max_joltage`] on the current cells.
+    pub fn max_joltage(&self, k: usize) -> Joltage {
+        self.select(k)
+            .map(|selection| selection.joltage)
+            .unwrap_or_default()
+    }
+
+    pub fn to_bank(&self) -> BatteryBank {
+        BatteryBank {
+            batteries: self
+                .blocks
+                .iter()
+                .flat_map(|block| block.batteries.iter().copied())
+                .collect(),
+        }
+    }
+
+    /// `(block, offset)` of the battery at `index`, which must be in bounds.
+    fn locate(&self, mut index: usize) -> (usize, usize) {
+        for (block, Block { batteries, .. }) in self.blocks.iter().enumerate() {
+            if index < batteries.len() {
+                return (block, index);
+            }
+
+            index -= batteries.len();
+        }
+
+        unreachable!("index checked against len")
+    }
+
+    /// Position and joltage of the first strongest battery in `l..=r`;
+    /// the scan stops early once it finds one as strong as `top`.
+    fn max_position(&self, l: usize, r: usize, top: u32) -> (usize, u32) {
+        let mut best: Option<(usize, u32)> = None;
+        let mut block_start = 0;
+
+        for block in &self.blocks {
+            let block_end = block_start + block.batteries.len();
+            if block_end <= l {
+                block_start = block_end;
+                continue;
+            }
+
+            if block_start > r || best.is_some_and(|(_, joltage)| joltage == top) {
+                break;
+            }
+
+            // a block can only improve on the current best when its maximum
+            // is strictly larger, which lets most blocks be skipped unseen.
+            if best.is_none_or(|(_, joltage)| block.max > joltage) {
+                let from = l.max(block_start) - block_start;
+                let to = r.min(block_end - 1) - block_start;
+
+                for (offset, battery) in block.batteries[from..=to].iter().enumerate() {
+                    if best.is_none_or(|(_, joltage)| battery.joltage > joltage) {
+                        best = Some((block_start + from + offset, battery.joltage));
+                    }
+                }
+            }
+
+            block_start = block_end;
+        }
+
+        best.expect("query range is never empty")
+    }
+}
+
+impl Block {
+    fn new(batteries: Vec<Battery>) -> Self {
+        let mut block = Self { batteries, max: 0 };
+        block.refresh_max();
+
+        block
+    }
+
+    fn refresh_max(&mut self) {
+        self.max = self.batteries.iter().map(|b| b.joltage).max().unwrap_or(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::INPUT_TEXT;
+    use crate::test_util::XorShift;
+
+    #[test]
+    fn test_dynamic_bank_updates() {
+        let bank: BatteryBank = "811111111111119".parse().unwrap();
+        let mut dynamic = DynamicBatteryBank::with_block_size(bank, 4);
+
+        assert_eq!(dynamic.max_joltage(2), Joltage::from(89usize));
+
+        assert_eq!(dynamic.replace(3, 9).joltage, 1);
+        assert_eq!(dynamic.max_joltage(2), Joltage::from(99usize));
+
+        assert_eq!(dynamic.remove(14).joltage, 9);
+        dynamic.insert(0, 7);
+        assert_eq!(dynamic.max_joltage(3), Joltage::from(911usize));
+        assert_eq!(dynamic.len(), 15);
+        assert_eq!(dynamic.to_bank(), "781191111111111".parse().unwrap());
+    }
+
+    #[test]
+    fn test_dynamic_bank_matches_rebuild() {
+        let mut rng = XorShift(0x9e37_79b9_7f4a_7c15);
+        let bank = BatteryBank::parse_all(INPUT_TEXT).unwrap().swap_remove(3);
+
+        let digits: &[u32] = &[0, 1, 5, 9];
+        let mixed: &[u32] = &[0, 1, 5, 9, 10, 23];
+
+        for (block_size, joltages) in [(1, digits), (3, digits), (16, mixed), (512, digits)] {
+            let mut dynamic = DynamicBatteryBank::with_block_size(bank.clone(), block_size);
+            let mut expected = bank.clone();
+
+            for _ in 0..400 {
+                let len = expected.batteries.len();
+                let joltage = rng.pick(joltages);
+
+                match rng.next(3) {
+                    0 if len > 0 => {
+                        let index = rng.next(len);
+                        assert_eq!(dynamic.replace(index, joltage), expected.batteries[index]);
+                        expected.batteries[index] = Battery { joltage };
+                    }
+                    1 if len > 0 => {
+                        let index = rng.next(len);
+                        assert_eq!(dynamic.remove(index), expected.batteries.remove(index));
+                    }
+                    _ => {
+                        let index = rng.next(len + 1);
+                        dynamic.insert(index, joltage);
+                        expected.batteries.insert(index, Battery { joltage });
+                    }
+                }
+
+                let k = rng.next(20);
+                assert_eq!(
+                    dynamic.select(k),
+                    expected.select(k),
+                    "block size {block_size}"
+                );
+            }
+
+            assert_eq!(dynamic.to_bank(), expected);
+        }
+    }
+
+    #[test]
+    fn test_dynamic_bank_keeps_blocks_near_sqrt() {
+        let mut dynamic = DynamicBatteryBank::from(BatteryBank { batteries: vec![] });
+        let balanced = |dynamic: &DynamicBatteryBank| {
+            let root = dynamic.len().isqrt().max(1);
+            dynamic.block_size <= 2 * root + 1
+                && 4 * dynamic.block_size >= root
+                && dynamic.blocks.len() <= 2 * (dynamic.len() / dynamic.block_size + 1)
+        };
+
+        for i in 0..20_000 {
+            dynamic.insert(i / 2, (i % 10) as u32);
+            assert!(balanced(&dynamic), "len {}", dynamic.len());
+        }
+
+        while dynamic.len() > 3 {
+            dynamic.remove(dynamic.len() / 3);
+            assert!(balanced(&dynamic), "len {}", dynamic.len());
+        }
+    }
+
+    #[test]
+    fn test_bank_edits() {
+        let mut dynamic = DynamicBatteryBank::from("8119".parse::<BatteryBank>().unwrap());
+
+        for edit in ["set:1:9", "insert:4:7", "remove:0"] {
+            dynamic.apply(edit.parse().unwrap()).unwrap();
+        }
+
+        assert_eq!(dynamic.to_bank(), "9197".parse().unwrap());
+        assert_eq!(
+            dynamic.apply(BankEdit::Remove { index: 4 }),
+            Err("edit position out of bounds")
+        );
+        assert_eq!("insert:1".parse::<BankEdit>(), Err("edit needs a joltage"));
+        assert_eq!(
+            "remove:1:2".parse::<BankEdit>(),
+            Err("remove takes no joltage")
+        );
+        assert_eq!("swap:1:2".parse::<BankEdit>(), Err("unknown edit"));
+        assert_eq!("set:x:2".parse::<BankEdit>(), Err("invalid position"));
+    }
+
+    #[test]
+    fn test_dynamic_bank_drains_and_refills() {
+        let bank: BatteryBank = "4242".parse().unwrap();
+        let mut dynamic = DynamicBatteryBank::with_block_size(bank, 2);
+
+        while dynamic.len() > 0 {
+            dynamic.remove(0);
+        }
+
+        assert_eq!(dynamic.select(1), None);
+        dynamic.insert(0, 5);
+        assert_eq!(dynamic.max_joltage(1), Joltage::from(5usize));
+        assert_eq!(dynamic.get(0), Some(Battery { joltage: 5 }));
+        assert_eq!(dynamic.get(1), None);
+    }
+}
@@ -0,0 +1,186 @@
+use super::{BatteryBank, Joltage, Selection};
+
+/// How many batteries a global selection may take from one bank.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BankQuota {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Batteries chosen across several banks, and the power they give in total.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlobalSelection {
+    /// One selection per bank, empty for banks that contribute nothing.
+    pub banks: Vec<Selection>,
+    /// Sum of every bank's joltage.
+    pub total: Joltage,
+}
+
+impl Default for BankQuota {
+    fn default() -> Self {
+        Self {
+            min: 0,
+            max: usize::MAX,
+        }
+    }
+}
+
+/// Chooses exactly `k` batteries across `banks`, each bank keeping within
+/// its entry of `quotas`, so that the banks' joltages add up to the most.
+/// Returns `None` when the quotas cannot be met with `k` batteries.
+///
+/// Each bank's best joltage is worked out only for the sizes its quota and
+/// `k` allow, leaving a knapsack over banks: O(banks · k · quota) joltage
+/// additions.
+///
+/// # Panics
+///
+/// Panics if `quotas` and `banks` differ in length.
+pub fn select_across(
+    banks: &[BatteryBank],
+    k: usize,
+    quotas: &[BankQuota],
+) -> Option<GlobalSelection> {
+    assert_eq!(banks.len(), quotas.len(), "one quota per bank");
+
+    // best[t]: largest total using `t` batteries from the banks seen so far;
+    // taken[b][t]: how many of those came from bank `b`.
+    let mut best: Vec<Option<Joltage>> = vec![None; k + 1];
+    best[0] = Some(Joltage::default());
+    let mut taken = Vec::with_capacity(banks.len());
+
+    for (bank, quota) in banks.iter().zip(quotas) {
+        let max = quota.max.min(bank.batteries.len()).min(k);
+        let sizes: Vec<usize> = (0..=max).collect();
+        let joltages = bank.max_joltages(&sizes);
+
+        let mut next: Vec<Option<Joltage>> = vec![None; k + 1];
+        let mut counts = vec![0; k + 1];
+
+        for (t, total) in best.iter().enumerate() {
+            let Some(total) = total else {
+                continue;
+            };
+
+            for j in quota.min..=max.min(k - t) {
+                let candidate = total.clone() + joltages[j].clone();
+                if next[t + j]
+                    .as_ref()
+                    .is_none_or(|current| candidate > *current)
+                {
+                    next[t + j] = Some(candidate);
+                    counts[t + j] = j;
+                }
+            }
+        }
+
+        best = next;
+        taken.push(counts);
+    }
+
+    let total = best[k].take()?;
+
+    let mut remaining = k;
+    let mut selections = vec![None; banks.len()];
+    for (b, bank) in banks.iter().enumerate().rev() {
+        let j = taken[b][remaining];
+        selections[b] = bank.select(j);
+        remaining -= j;
+    }
+
+    Some(GlobalSelection {
+        banks: selections.into_iter().map(Option::unwrap).collect(),
+        total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::XorShift;
+
+    const EXAMPLE: &str = "
+        987654321111111
+        811111111111119
+        234234234234278
+        818181911112111
+    ";
+
+    fn brute_force(banks: &[BatteryBank], k: usize, quotas: &[BankQuota]) -> Option<Joltage> {
+        let Some((bank, rest)) = banks.split_first() else {
+            return (k == 0).then(Joltage::default);
+        };
+
+        let max = quotas[0].max.min(bank.batteries.len()).min(k);
+        (quotas[0].min..=max)
+            .filter_map(|j| Some(bank.max_joltage(j) + brute_force(rest, k - j, &quotas[1..])?))
+            .max()
+    }
+
+    fn count(selection: &GlobalSelection) -> usize {
+        selection.banks.iter().map(|bank| bank.indices.len()).sum()
+    }
+
+    #[test]
+    fn test_select_across_example() {
+        let banks = BatteryBank::parse_all(EXAMPLE).unwrap();
+        let quotas = [BankQuota { min: 0, max: 12 }; 4];
+
+        let all = select_across(&banks, 48, &quotas).unwrap();
+        assert_eq!(all.total, Joltage::from(3121910778619usize));
+
+        let one = select_across(&banks, 2, &[BankQuota::default(); 4]).unwrap();
+        assert_eq!(one.total, Joltage::from(98usize));
+        assert_eq!(one.banks[0].indices, vec![0, 1]);
+        assert_eq!(count(&one), 2);
+
+        let spread = select_across(&banks, 4, &[BankQuota { min: 1, max: 1 }; 4]).unwrap();
+        assert_eq!(spread.total, Joltage::from(9usize + 9 + 8 + 9));
+        assert!(spread.banks.iter().all(|bank| bank.indices.len() == 1));
+
+        assert_eq!(
+            select_across(&banks, 3, &[BankQuota { min: 1, max: 2 }; 4]),
+            None
+        );
+        assert_eq!(select_across(&banks, 61, &[BankQuota::default(); 4]), None);
+    }
+
+    #[test]
+    fn test_select_across_against_brute_force() {
+        let mut rng = XorShift(0xda94_2042_e4dd_58b5);
+
+        for _ in 0..300 {
+            let banks: Vec<BatteryBank> = (0..1 + rng.next(4))
+                .map(|_| {
+                    let len = rng.next(6);
+                    rng.bank(len, &[0, 1, 3, 5, 8, 9, 10, 12, 40, 99])
+                })
+                .collect();
+            let quotas: Vec<BankQuota> = banks
+                .iter()
+                .map(|_| {
+                    let min = rng.next(3);
+                    BankQuota {
+                        min,
+                        max: min + rng.next(4),
+                    }
+                })
+                .collect();
+            let k = rng.next(12);
+
+            let selection = select_across(&banks, k, &quotas);
+            assert_eq!(
+                selection.as_ref().map(|s| s.total.clone()),
+                brute_force(&banks, k, &quotas),
+                "{banks:?} k={k} {quotas:?}"
+            );
+
+            if let Some(selection) = selection {
+                assert_eq!(count(&selection), k);
+
+                let sum: Joltage = selection.banks.iter().map(|s| &s.joltage).sum();
+                assert_eq!(sum, selection.total);
+            }
+        }
+    }
+}
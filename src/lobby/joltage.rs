@@ -0,0 +1,203 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Sum;
+use std::ops::{Add, AddAssign};
+use std::str::FromStr;
+
+/// An exact, non-negative decimal number of any length.
+///
+/// Selections of twenty or more batteries no longer fit in a `u64`, so
+/// joltages keep their decimal digits around.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Joltage {
+    /// Most significant digit first, without leading zeros; zero is empty.
+    digits: Vec<u8>,
+}
+
+impl Joltage {
+    pub fn from_digits(digits: &[u8]) -> Self {
+        debug_assert!(digits.iter().all(|&d| d <= 9));
+
+        let first = digits.iter().position(|&d| d != 0).unwrap_or(digits.len());
+
+        Self {
+            digits: digits[first..].to_vec(),
+        }
+    }
+
+    /// The number formed by writing `values` one after another in decimal,
+    /// so `[12, 7, 0]` gives 1270.
+    pub fn concat(values: &[u32]) -> Self {
+        let mut digits = Vec::with_capacity(values.len());
+        for value in values {
+            digits.extend(value.to_string().bytes().map(|b| b - b'0'));
+        }
+
+        Self::from_digits(&digits)
+    }
+}
+
+impl From<u128> for Joltage {
+    fn from(mut value: u128) -> Self {
+        let mut digits = Vec::new();
+        while value > 0 {
+            digits.push((value % 10) as u8);
+            value /= 10;
+        }
+
+        digits.reverse();
+        Self { digits }
+    }
+}
+
+impl From<usize> for Joltage {
+    fn from(value: usize) -> Self {
+        Self::from(value as u128)
+    }
+}
+
+impl FromStr for Joltage {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err("joltage must be a non-empty string of decimal digits");
+        }
+
+        let digits: Vec<u8> = s.bytes().map(|b| b - b'0').collect();
+        Ok(Self::from_digits(&digits))
+    }
+}
+
+impl Ord for Joltage {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.digits
+            .len()
+            .cmp(&other.digits.len())
+            .then_with(|| self.digits.cmp(&other.digits))
+    }
+}
+
+impl PartialOrd for Joltage {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl AddAssign<&Joltage> for Joltage {
+    fn add_assign(&mut self, rhs: &Joltage) {
+        let len = self.digits.len().max(rhs.digits.len());
+        let mut sum = Vec::with_capacity(len + 1);
+
+        let mut lhs_digits = self.digits.iter().rev();
+        let mut rhs_digits = rhs.digits.iter().rev();
+        let mut carry = 0;
+        for _ in 0..len {
+            let digit = lhs_digits.next().unwrap_or(&0) + rhs_digits.next().unwrap_or(&0) + carry;
+
+            sum.push(digit % 10);
+            carry = digit / 10;
+        }
+
+        if carry > 0 {
+            sum.push(carry);
+        }
+
+        sum.reverse();
+        self.digits = sum;
+    }
+}
+
+impl AddAssign for Joltage {
+    fn add_assign(&mut self, rhs: Self) {
+        *self += &rhs;
+    }
+}
+
+impl Add for Joltage {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += &rhs;
+        self
+    }
+}
+
+impl<'a> Sum<&'a Joltage> for Joltage {
+    fn sum<I: Iterator<Item = &'a Joltage>>(iter: I) -> Self {
+        iter.fold(Self::default(), |mut acc, joltage| {
+            acc += joltage;
+            acc
+        })
+    }
+}
+
+impl fmt::Display for Joltage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.digits.is_empty() {
+            return f.pad("0");
+        }
+
+        let text: String = self.digits.iter().map(|&d| (b'0' + d) as char).collect();
+        f.pad(&text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn joltage(s: &str) -> Joltage {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_from_digits_strips_leading_zeros() {
+        assert_eq!(Joltage::from_digits(&[0, 0, 4, 2]), joltage("42"));
+        assert_eq!(Joltage::from_digits(&[0, 0]), Joltage::default());
+        assert_eq!(Joltage::from_digits(&[]).to_string(), "0");
+    }
+
+    #[test]
+    fn test_concat() {
+        assert_eq!(Joltage::concat(&[12, 7, 0]), joltage("1270"));
+        assert_eq!(Joltage::concat(&[0, 0, 15]), joltage("15"));
+        assert_eq!(Joltage::concat(&[]), Joltage::default());
+    }
+
+    #[test]
+    fn test_usize_boundary() {
+        let max = Joltage::from(usize::MAX);
+
+        assert_eq!(max, joltage("18446744073709551615"));
+        assert_eq!(
+            (max + Joltage::from(1usize)).to_string(),
+            "18446744073709551616"
+        );
+        assert_eq!(
+            joltage("9999999999999999999"),
+            Joltage::from(9_999_999_999_999_999_999usize)
+        );
+    }
+
+    #[test]
+    fn test_u128_boundary() {
+        let max = Joltage::from(u128::MAX);
+
+        assert_eq!(max.to_string(), u128::MAX.to_string());
+        assert_eq!(
+            (max + Joltage::from(1usize)).to_string(),
+            "340282366920938463463374607431768211456"
+        );
+    }
+
+    #[test]
+    fn test_add_and_order() {
+        let sum: Joltage = [joltage("999"), joltage("1"), joltage("0")].iter().sum();
+
+        assert_eq!(sum, joltage("1000"));
+        assert!(joltage("1000") > joltage("999"));
+        assert!(joltage("998") < joltage("999"));
+        assert_eq!(format!("{:>6}", joltage("42")), "    42");
+    }
+}
@@ -1,75 +1,295 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt;
 use std::str::FromStr;
 
-const INPUT_TEXT: &str = include_str!("input.txt");
+mod constrained;
+mod dynamic;
+mod global;
+mod joltage;
+mod objective;
+mod query;
+mod streaming;
+
+pub use constrained::SelectionConstraints;
+pub use dynamic::{BankEdit, DynamicBatteryBank};
+pub use global::{BankQuota, select_across};
+pub use joltage::Joltage;
+pub use objective::Objective;
+pub use query::RangeIndex;
+pub use streaming::select_streaming;
+
+pub const INPUT_TEXT: &str = include_str!("input.txt");
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
-struct Battery {
-    pub joltage: u8,
+pub struct Battery {
+    pub joltage: u32,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct BatteryBank {
+pub struct BatteryBank {
     pub batteries: Vec<Battery>,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+/// Joltages rather than machine integers, since banks of multi-digit
+/// batteries can spell twelve-battery numbers well past `u64`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub struct BatteryPower {
-    pub max_2: usize,
-    pub max_12: usize,
+    pub max_2: Joltage,
+    pub max_12: Joltage,
+}
+
+/// Batteries picked out of a bank, by position, and the joltage they make.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    /// Ascending indices into [`BatteryBank::batteries`].
+    pub indices: Vec<usize>,
+    pub joltage: Joltage,
+}
+
+/// The best selection of a bank for every size, from a single sweep.
+///
+/// Dropping one battery from the best `k`-selection always yields the best
+/// `k - 1`-selection, so a single ordering of the batteries by when they get
+/// dropped describes every size at once.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PowerProfile {
+    joltages: Vec<u32>,
+    /// For each battery, how many batteries are dropped before it.
+    dropped_at: Vec<usize>,
+}
+
+/// Maximum joltage of every bank for each requested selection size.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SelectionReport {
+    pub sizes: Vec<usize>,
+    /// One row per bank, holding a joltage per entry of `sizes`.
+    pub banks: Vec<Vec<Joltage>>,
+    /// Sum over every bank, per entry of `sizes`.
+    pub totals: Vec<Joltage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BankParseError {
+    /// 1-based line of the offending bank.
+    pub line: usize,
+    pub reason: &'static str,
 }
 
 #[inline]
 #[must_use]
 pub fn solve() -> BatteryPower {
-    run_simulation(INPUT_TEXT)
+    run_simulation(INPUT_TEXT).expect("bundled input is a valid bank list")
 }
 
-#[must_use]
-fn run_simulation(input: &str) -> BatteryPower {
-    let banks = BatteryBank::parse_all(input);
+fn run_simulation(input: &str) -> Result<BatteryPower, BankParseError> {
+    let banks = BatteryBank::parse_all(input)?;
 
     let mut power = BatteryPower::default();
     for bank in &banks {
         let bank_power = bank.calculate_power();
 
-        power.max_2 += bank_power.max_2;
-        power.max_12 += bank_power.max_12;
+        power.max_2 += &bank_power.max_2;
+        power.max_12 += &bank_power.max_12;
     }
 
-    power
+    Ok(power)
+}
+
+#[must_use]
+pub fn run_selection(banks: &[BatteryBank], sizes: &[usize]) -> SelectionReport {
+    let mut report = SelectionReport {
+        sizes: sizes.to_vec(),
+        banks: Vec::with_capacity(banks.len()),
+        totals: vec![Joltage::default(); sizes.len()],
+    };
+
+    for bank in banks {
+        let row = bank.max_joltages(sizes);
+        for (total, joltage) in report.totals.iter_mut().zip(&row) {
+            *total += joltage;
+        }
+
+        report.banks.push(row);
+    }
+
+    report
 }
 
 impl BatteryBank {
-    pub fn parse_all(input: &str) -> Vec<Self> {
-        input.lines().filter_map(|line| line.parse().ok()).collect()
+    /// Parses one bank per line, skipping blank lines. See
+    /// [`BatteryBank::from_str`] for the accepted formats.
+    pub fn parse_all(input: &str) -> Result<Vec<Self>, BankParseError> {
+        input
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(index, line)| {
+                line.parse().map_err(|reason| BankParseError {
+                    line: index + 1,
+                    reason,
+                })
+            })
+            .collect()
     }
 
     pub fn calculate_power(&self) -> BatteryPower {
-        let values: Vec<u8> = self.batteries.iter().map(|b| b.joltage).collect();
+        BatteryPower {
+            max_2: self.max_joltage(2),
+            max_12: self.max_joltage(12),
+        }
+    }
+
+    /// Largest joltage reachable by switching on exactly `k` batteries, or 0
+    /// when the bank holds fewer than `k`.
+    pub fn max_joltage(&self, k: usize) -> Joltage {
+        Joltage::concat(&Self::max_subsequence(&self.joltages(), k))
+    }
 
-        let max_2_vec = Self::max_subsequence(&values, 2);
-        let max_12_vec = Self::max_subsequence(&values, 12);
+    /// [`Self::max_joltage`] for each of `sizes`, in the same order.
+    pub fn max_joltages(&self, sizes: &[usize]) -> Vec<Joltage> {
+        let values = self.joltages();
 
-        BatteryPower {
-            max_2: Self::vec_to_usize(&max_2_vec),
-            max_12: Self::vec_to_usize(&max_12_vec),
+        sizes
+            .iter()
+            .map(|&k| Joltage::concat(&Self::max_subsequence(&values, k)))
+            .collect()
+    }
+
+    fn joltages(&self) -> Vec<u32> {
+        self.batteries.iter().map(|b| b.joltage).collect()
+    }
+
+    /// Whether every joltage is a single digit.
+    ///
+    /// Only then does comparing batteries one against another order the
+    /// numbers they form: in `9,12,5` the larger 12 leads to 125, while the
+    /// 9 leads to 912. The greedy selections, and the indexes built on them,
+    /// rely on that comparison, so other banks spell candidate selections
+    /// out instead.
+    pub fn is_single_digit(&self) -> bool {
+        self.batteries.iter().all(|b| b.joltage <= 9)
+    }
+
+    /// The batteries giving the largest joltage for exactly `k` of them, or
+    /// `None` when the bank holds fewer than `k`.
+    pub fn select(&self, k: usize) -> Option<Selection> {
+        let values = self.joltages();
+        if k > values.len() {
+            return None;
+        }
+
+        let indices = Self::max_subsequence_indices(&values, k);
+        let chosen: Vec<u32> = indices.iter().map(|&i| values[i]).collect();
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+
+    /// Computes the best selection of every size, in O(n) for single-digit
+    /// banks.
+    pub fn power_profile(&self) -> PowerProfile {
+        let values = self.joltages();
+        if !self.is_single_digit() {
+            return PowerProfile {
+                dropped_at: concat_drop_order(&values),
+                joltages: values,
+            };
+        }
+
+        let mut dropped_at = vec![0; values.len()];
+        let mut dropped = 0;
+
+        // the same monotonic stack as `max_subsequence_indices`, but with an
+        // unlimited drop budget: the order in which batteries leave the stack
+        // is exactly the order in which shrinking selections give them up.
+        let mut stack: Vec<usize> = Vec::with_capacity(values.len());
+        for (i, &val) in values.iter().enumerate() {
+            while let Some(&top) = stack.last() {
+                if val <= values[top] {
+                    break;
+                }
+
+                dropped_at[stack.pop().unwrap()] = dropped;
+                dropped += 1;
+            }
+
+            stack.push(i);
+        }
+
+        while let Some(top) = stack.pop() {
+            dropped_at[top] = dropped;
+            dropped += 1;
+        }
+
+        PowerProfile {
+            joltages: values,
+            dropped_at,
         }
     }
 
-    fn max_subsequence(values: &[u8], k: usize) -> Vec<u8> {
+    /// The bank line with a second line marking the selected batteries.
+    ///
+    /// ```text
+    /// 811111111111119
+    /// ^             ^
+    /// ```
+    ///
+    /// Banks holding multi-digit joltages are written comma-delimited, with
+    /// the mark under the first digit of each chosen battery.
+    pub fn render_selection(&self, selection: &Selection) -> String {
+        let delimited = self.batteries.iter().any(|b| b.joltage > 9);
+        let mut line = String::with_capacity(self.batteries.len());
+        let mut marks = String::with_capacity(self.batteries.len());
+        let mut chosen = selection.indices.iter().peekable();
+
+        for (i, battery) in self.batteries.iter().enumerate() {
+            if delimited && i > 0 {
+                line.push(',');
+                marks.push(' ');
+            }
+
+            let value = battery.joltage.to_string();
+            line.push_str(&value);
+            marks.push(if chosen.next_if_eq(&&i).is_some() {
+                '^'
+            } else {
+                ' '
+            });
+            marks.extend(std::iter::repeat_n(' ', value.len() - 1));
+        }
+
+        format!("{line}\n{}", marks.trim_end())
+    }
+
+    fn max_subsequence(values: &[u32], k: usize) -> Vec<u32> {
+        Self::max_subsequence_indices(values, k)
+            .into_iter()
+            .map(|i| values[i])
+            .collect()
+    }
+
+    fn max_subsequence_indices(values: &[u32], k: usize) -> Vec<usize> {
         let n = values.len();
         if k > n {
             return vec![];
         }
 
+        if values.iter().any(|&v| v > 9) {
+            return concat_indices(values, k, |_| 0, Ordering::Greater)
+                .expect("a bank holding k batteries has a largest selection");
+        }
+
         let mut drop_budget = n - k;
-        let mut stack: Vec<u8> = Vec::with_capacity(k);
+        let mut stack: Vec<usize> = Vec::with_capacity(k);
 
-        for val in values {
+        for (i, val) in values.iter().enumerate() {
             while drop_budget > 0 {
                 if let Some(&top) = stack.last() {
-                    if *val > top {
+                    if *val > values[top] {
                         stack.pop();
                         drop_budget -= 1;
                     } else {
@@ -80,34 +300,285 @@ impl BatteryBank {
                 }
             }
 
-            stack.push(*val);
+            stack.push(i);
         }
 
         stack.truncate(k);
         stack
     }
+}
+
+/// Orders digit strings that follow a nonzero digit, where every digit
+/// counts and a longer string is always the larger one.
+fn cmp_spelled(a: &[u8], b: &[u8]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
 
-    fn vec_to_usize(digits: &[u8]) -> usize {
-        digits.iter().fold(0, |acc, &d| (acc * 10) + d as usize)
+/// Orders digit strings by the numbers they spell, leading zeros ignored.
+fn cmp_numbers(a: &[u8], b: &[u8]) -> Ordering {
+    let strip = |s: &[u8]| s.iter().position(|&d| d != b'0').map_or(0, |i| s.len() - i);
+
+    cmp_spelled(&a[a.len() - strip(a)..], &b[b.len() - strip(b)..])
+}
+
+/// Stores `candidate` in `slot` unless `slot` already holds a value that
+/// compares as `want` against it, returning whether it did; ties go to the
+/// candidate.
+fn keep_better<T>(
+    slot: &mut Option<T>,
+    candidate: Option<T>,
+    cmp: impl Fn(&T, &T) -> Ordering,
+    want: Ordering,
+) -> bool {
+    let Some(candidate) = candidate else {
+        return false;
+    };
+
+    if slot
+        .as_ref()
+        .is_some_and(|current| cmp(current, &candidate) == want)
+    {
+        return false;
     }
+
+    *slot = Some(candidate);
+    true
+}
+
+fn spell(values: &[u32]) -> Vec<Vec<u8>> {
+    values.iter().map(|v| v.to_string().into_bytes()).collect()
 }
 
+/// `digits` followed by `rest`, when there is a `rest`.
+fn prepend(digits: &[u8], rest: Option<&Vec<u8>>) -> Option<Vec<u8>> {
+    rest.map(|rest| [digits, rest].concat())
+}
+
+/// Selection scores: a sum over the chosen batteries, then the digits they
+/// spell.
+type Spelled = (u64, Vec<u8>);
+
+/// Picks `k` batteries from a bank that is not
+/// [single-digit](BatteryBank::is_single_digit).
+///
+/// Selections compare by the sum of `score` over their batteries, then by
+/// the number they spell, and `want` asks for the largest or the smallest.
+/// The largest may open with 0 batteries, which add nothing to the number;
+/// the smallest may not, so it is `None` when every battery is a 0.
+///
+/// This works out, from the back, the best string of every length that each
+/// suffix can spell: O(n · k) comparisons of strings up to the selection's
+/// length.
+fn concat_indices(
+    values: &[u32],
+    k: usize,
+    score: fn(u32) -> u64,
+    want: Ordering,
+) -> Option<Vec<usize>> {
+    let n = values.len();
+    let digits = spell(values);
+    let cmp_tail = |a: &Spelled, b: &Spelled| a.0.cmp(&b.0).then_with(|| cmp_spelled(&a.1, &b.1));
+    let cmp_front = |a: &Spelled, b: &Spelled| a.0.cmp(&b.0).then_with(|| cmp_numbers(&a.1, &b.1));
+
+    // best[j]: the best string `j` batteries of the current suffix spell
+    // after a nonzero digit, where a leading 0 still counts; leading[j]: the
+    // best number they spell at the very front, where it does not.
+    // took_*[i][j]: whether that string for the suffix at `i` uses battery `i`.
+    let mut best: Vec<Option<Spelled>> = vec![None; k + 1];
+    best[0] = Some((0, Vec::new()));
+    let mut leading = best.clone();
+    let mut took_best = vec![false; n * (k + 1)];
+    let mut took_leading = took_best.clone();
+
+    for i in (0..n).rev() {
+        let extend = |rest: &Option<Spelled>| {
+            rest.as_ref().map(|(total, rest)| {
+                (
+                    total + score(values[i]),
+                    [digits[i].as_slice(), rest].concat(),
+                )
+            })
+        };
+
+        // longest selections first, so `[j - 1]` still describes `i + 1..`
+        for j in (1..=k.min(n - i)).rev() {
+            let take = extend(&best[j - 1]);
+            let take_leading = match values[i] {
+                0 if want.is_gt() => extend(&leading[j - 1]),
+                0 => None,
+                _ => take.clone(),
+            };
+
+            took_best[i * (k + 1) + j] = keep_better(&mut best[j], take, cmp_tail, want);
+            took_leading[i * (k + 1) + j] =
+                keep_better(&mut leading[j], take_leading, cmp_front, want);
+        }
+    }
+
+    leading[k].as_ref()?;
+
+    let mut indices = Vec::with_capacity(k);
+    let mut at_front = true;
+    for i in 0..n {
+        let remaining = k - indices.len();
+        let took = if at_front { &took_leading } else { &took_best };
+
+        if remaining > 0 && took[i * (k + 1) + remaining] {
+            indices.push(i);
+            at_front &= values[i] == 0;
+        }
+    }
+
+    Some(indices)
+}
+
+/// [`PowerProfile`] drop order for multi-digit joltages: each step drops
+/// the battery whose removal leaves the largest number, preferring the
+/// earliest.
+///
+/// Only a shortest battery is worth dropping, as anything else loses a digit
+/// more. Dropping the one spelled from offset `p` shifts the digits after it
+/// left by its length `s`, so it grows the number if the first digit to
+/// change under that shift goes up. The earliest such battery is the best
+/// drop. Failing that, the best is the earliest one whose shift changes
+/// nothing before the last candidate's offset. Finding the first change past
+/// each offset is one backward sweep, so a step costs O(n · digits) and the
+/// whole order O(n² · digits).
+fn concat_drop_order(values: &[u32]) -> Vec<usize> {
+    let digits = spell(values);
+    let mut kept: Vec<usize> = (0..values.len()).collect();
+    let mut dropped_at = vec![0; values.len()];
+
+    for dropped in 0..values.len() {
+        let shortest = kept.iter().map(|&i| digits[i].len()).min().unwrap();
+        let spelled: Vec<u8> = kept
+            .iter()
+            .flat_map(|&i| digits[i].iter().copied())
+            .collect();
+
+        let mut starts = Vec::with_capacity(kept.len());
+        let mut offset = 0;
+        for &i in &kept {
+            starts.push(offset);
+            offset += digits[i].len();
+        }
+
+        // changed[y]: the first offset at or after `y` whose digit changes
+        // when the ones after it shift left by `shortest`.
+        let mut changed = vec![None; spelled.len() + 1];
+        for y in (0..spelled.len()).rev() {
+            changed[y] = match spelled.get(y + shortest) {
+                Some(&next) if next != spelled[y] => Some(y),
+                _ => changed[y + 1],
+            };
+        }
+
+        let candidates: Vec<usize> = (0..kept.len())
+            .filter(|&pos| digits[kept[pos]].len() == shortest)
+            .collect();
+        let last = starts[*candidates.last().unwrap()];
+        let pos = candidates
+            .iter()
+            .copied()
+            .find(|&pos| changed[starts[pos]].is_some_and(|y| spelled[y + shortest] > spelled[y]))
+            .or_else(|| {
+                candidates
+                    .iter()
+                    .copied()
+                    .find(|&pos| changed[starts[pos]].is_none_or(|y| y >= last))
+            })
+            .unwrap();
+
+        dropped_at[kept.remove(pos)] = dropped;
+    }
+
+    dropped_at
+}
+
+impl PowerProfile {
+    /// Largest selection size, i.e. the number of batteries in the bank.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.joltages.len()
+    }
+
+    pub fn selection(&self, k: usize) -> Option<Selection> {
+        if k > self.len() {
+            return None;
+        }
+
+        let kept_from = self.len() - k;
+        let indices: Vec<usize> = (0..self.len())
+            .filter(|&i| self.dropped_at[i] >= kept_from)
+            .collect();
+        let chosen: Vec<u32> = indices.iter().map(|&i| self.joltages[i]).collect();
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+
+    pub fn joltage(&self, k: usize) -> Option<Joltage> {
+        self.selection(k).map(|selection| selection.joltage)
+    }
+
+    /// `(k, joltage)` for every selection size from 1 to [`Self::len`].
+    pub fn iter(&self) -> impl Iterator<Item = (usize, Joltage)> + '_ {
+        (1..=self.len()).filter_map(|k| Some((k, self.joltage(k)?)))
+    }
+}
+
+/// Accepts either one digit per battery (`987654321111111`) or joltages
+/// of any size separated by commas (`12,7,15,3`). A selection's joltage is
+/// its batteries' values written one after another, so picking 12 and 7
+/// gives 127, and the best selection is the one spelling the largest number.
 impl FromStr for BatteryBank {
     type Err = &'static str;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let batteries = s
-            .chars()
-            .filter_map(|c| c.to_digit(10).map(|d| Battery { joltage: d as u8 }))
-            .collect();
+        let s = s.trim();
+
+        let batteries = if s.contains(',') {
+            s.split(',')
+                .map(|value| {
+                    let value = value.trim();
+                    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+                        return Err("invalid joltage");
+                    }
+
+                    value
+                        .parse()
+                        .map(|joltage| Battery { joltage })
+                        .map_err(|_| "joltage too large")
+                })
+                .collect::<Result<_, _>>()?
+        } else {
+            s.chars()
+                .map(|c| {
+                    c.to_digit(10)
+                        .map(|joltage| Battery { joltage })
+                        .ok_or("unrecognized character in bank")
+                })
+                .collect::<Result<_, _>>()?
+        };
 
         Ok(BatteryBank { batteries })
     }
 }
 
+impl fmt::Display for BankParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.reason)
+    }
+}
+
+impl Error for BankParseError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_util::XorShift;
 
     #[test]
     fn test_max_2_example() {
@@ -118,15 +589,16 @@ mod tests {
             234234234234278
             818181911112111
         ",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(power.max_2, 357);
+        assert_eq!(power.max_2, Joltage::from(357usize));
     }
 
     #[test]
     fn test_max_2() {
-        let power = run_simulation(INPUT_TEXT);
-        assert_eq!(power.max_2, 17403);
+        let power = run_simulation(INPUT_TEXT).unwrap();
+        assert_eq!(power.max_2, Joltage::from(17403usize));
     }
 
     #[test]
@@ -138,14 +610,241 @@ mod tests {
             234234234234278
             818181911112111
         ",
-        );
+        )
+        .unwrap();
 
-        assert_eq!(power.max_12, 3121910778619);
+        assert_eq!(power.max_12, Joltage::from(3121910778619usize));
     }
 
     #[test]
     fn test_max_12() {
-        let power = run_simulation(INPUT_TEXT);
-        assert_eq!(power.max_12, 173416889848394);
+        let power = run_simulation(INPUT_TEXT).unwrap();
+        assert_eq!(power.max_12, Joltage::from(173416889848394usize));
+    }
+
+    #[test]
+    fn test_power_past_usize() {
+        let power = run_simulation("4000000000,4000000000,1").unwrap();
+
+        assert_eq!(power.max_2, "40000000004000000000".parse().unwrap());
+        assert_eq!(power.max_12, Joltage::default());
+    }
+
+    #[test]
+    fn test_run_selection_example() {
+        let banks = BatteryBank::parse_all(
+            "
+            987654321111111
+            811111111111119
+            234234234234278
+            818181911112111
+        ",
+        )
+        .unwrap();
+        let report = run_selection(&banks, &[1, 2, 5, 12, 15, 16]);
+
+        let joltages = |values: &[usize]| -> Vec<Joltage> {
+            values.iter().map(|&v| Joltage::from(v)).collect()
+        };
+
+        assert_eq!(
+            report.banks[0],
+            joltages(&[9, 98, 98765, 987654321111, 987654321111111, 0])
+        );
+        assert_eq!(
+            report.banks[1],
+            joltages(&[9, 89, 81119, 811111111119, 811111111111119, 0])
+        );
+        assert_eq!(report.totals[1], Joltage::from(357usize));
+        assert_eq!(report.totals[3], Joltage::from(3121910778619usize));
+    }
+
+    #[test]
+    fn test_select_indices() {
+        let bank: BatteryBank = "818181911112111".parse().unwrap();
+        let selection = bank.select(12).unwrap();
+
+        assert_eq!(
+            selection.indices,
+            vec![0, 2, 4, 6, 7, 8, 9, 10, 11, 12, 13, 14]
+        );
+        assert_eq!(selection.joltage, Joltage::from(888911112111usize));
+        assert_eq!(bank.select(16), None);
+    }
+
+    #[test]
+    fn test_render_selection() {
+        let bank: BatteryBank = "811111111111119".parse().unwrap();
+        let selection = bank.select(2).unwrap();
+
+        assert_eq!(
+            bank.render_selection(&selection),
+            "811111111111119\n^             ^"
+        );
+    }
+
+    #[test]
+    fn test_select_matches_max_joltage() {
+        for bank in BatteryBank::parse_all(INPUT_TEXT).unwrap() {
+            for k in [1, 2, 12, 50] {
+                let selection = bank.select(k).unwrap();
+                let chosen: Vec<u32> = selection
+                    .indices
+                    .iter()
+                    .map(|&i| bank.batteries[i].joltage)
+                    .collect();
+
+                assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(Joltage::concat(&chosen), bank.max_joltage(k));
+                assert_eq!(selection.joltage, bank.max_joltage(k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_power_profile_matches_select() {
+        let banks = BatteryBank::parse_all(INPUT_TEXT).unwrap();
+
+        for bank in banks.iter().take(20) {
+            let profile = bank.power_profile();
+
+            assert_eq!(profile.len(), bank.batteries.len());
+            for k in 0..=bank.batteries.len() + 1 {
+                assert_eq!(profile.selection(k), bank.select(k), "k = {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_power_profile_example() {
+        let bank: BatteryBank = "234234234234278".parse().unwrap();
+        let profile: Vec<_> = bank
+            .power_profile()
+            .iter()
+            .map(|(k, joltage)| (k, joltage.to_string()))
+            .take(4)
+            .collect();
+
+        assert_eq!(
+            profile,
+            vec![
+                (1, "8".to_owned()),
+                (2, "78".to_owned()),
+                (3, "478".to_owned()),
+                (4, "4478".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_delimited_bank() {
+        let bank: BatteryBank = " 12, 7,15,3 ".parse().unwrap();
+        let selection = bank.select(2).unwrap();
+
+        assert_eq!(selection.indices, vec![0, 2]);
+        assert_eq!(selection.joltage, Joltage::from(1215usize));
+        assert_eq!(bank.max_joltage(3), Joltage::from(12715usize));
+        assert_eq!(bank.render_selection(&selection), "12,7,15,3\n^    ^");
+        assert_eq!("1,2".parse(), Ok("12".parse::<BatteryBank>().unwrap()));
+    }
+
+    #[test]
+    fn test_multi_digit_selection() {
+        let bank: BatteryBank = "9,12,5".parse().unwrap();
+        assert_eq!(bank.max_joltage(2), Joltage::from(912usize));
+        assert_eq!(bank.select(2).unwrap().indices, vec![0, 1]);
+
+        let bank: BatteryBank = "9,10".parse().unwrap();
+        assert_eq!(bank.max_joltage(1), Joltage::from(10usize));
+
+        let bank: BatteryBank = "0,0,12,93".parse().unwrap();
+        assert_eq!(bank.max_joltage(3), Joltage::from(1293usize));
+        assert_eq!(bank.max_joltage(2), Joltage::from(1293usize));
+        assert_eq!(bank.select(2).unwrap().indices, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_multi_digit_against_brute_force() {
+        let mut rng = XorShift(0x6a09_e667_f3bc_c908);
+
+        for _ in 0..1000 {
+            let n = rng.next(9);
+            let bank = rng.bank(n, &[0, 0, 1, 3, 5, 9, 10, 12, 40, 99]);
+            let profile = bank.power_profile();
+
+            for k in 0..=n {
+                let expected = (0u32..1 << n)
+                    .filter(|mask| mask.count_ones() as usize == k)
+                    .map(|mask| {
+                        let chosen: Vec<u32> = (0..n)
+                            .filter(|i| mask & (1 << i) != 0)
+                            .map(|i| bank.batteries[i].joltage)
+                            .collect();
+                        Joltage::concat(&chosen)
+                    })
+                    .max()
+                    .unwrap();
+
+                let selection = bank.select(k).unwrap();
+                let chosen: Vec<u32> = selection
+                    .indices
+                    .iter()
+                    .map(|&i| bank.batteries[i].joltage)
+                    .collect();
+
+                assert!(selection.indices.windows(2).all(|w| w[0] < w[1]));
+                assert_eq!(Joltage::concat(&chosen), expected, "{bank:?} k={k}");
+                assert_eq!(selection.joltage, expected, "{bank:?} k={k}");
+                assert_eq!(bank.max_joltage(k), expected, "{bank:?} k={k}");
+                assert_eq!(profile.joltage(k), Some(expected.clone()),);
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_bank_errors() {
+        assert_eq!(
+            "12a4".parse::<BatteryBank>(),
+            Err("unrecognized character in bank")
+        );
+        assert_eq!("1,,2".parse::<BatteryBank>(), Err("invalid joltage"));
+        assert_eq!("1,-2".parse::<BatteryBank>(), Err("invalid joltage"));
+        assert_eq!(
+            "99999999999,1".parse::<BatteryBank>(),
+            Err("joltage too large")
+        );
+        assert_eq!(
+            BatteryBank::parse_all("123\n\n  \n4 5"),
+            Err(BankParseError {
+                line: 4,
+                reason: "unrecognized character in bank"
+            })
+        );
+    }
+
+    #[test]
+    fn test_selection_past_usize() {
+        let banks = BatteryBank::parse_all("99999999999999999999\n12345678901234567890").unwrap();
+        let report = run_selection(&banks, &[19, 20]);
+
+        assert_eq!(
+            report.banks[0][0],
+            Joltage::from(9_999_999_999_999_999_999usize)
+        );
+        assert_eq!(report.banks[0][1].to_string(), "99999999999999999999");
+        assert_eq!(report.totals[1].to_string(), "112345678901234567889");
+        assert_eq!(
+            report.totals[1],
+            Joltage::from(112_345_678_901_234_567_889u128)
+        );
+    }
+
+    #[test]
+    fn test_run_selection_matches_power() {
+        let banks = BatteryBank::parse_all(INPUT_TEXT).unwrap();
+        let report = run_selection(&banks, &[12, 2]);
+        let power = solve();
+
+        assert_eq!(report.totals, vec![power.max_12, power.max_2]);
     }
 }
@@ -0,0 +1,572 @@
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+use super::{BatteryBank, Joltage, Selection, concat_indices, spell};
+
+/// What a selection of `k` batteries should optimize for. Selections are
+/// worth the number their joltages spell, so `12,3` is worth 123.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Objective {
+    /// The largest number, as picked by [`BatteryBank::select`].
+    #[default]
+    Largest,
+    /// The smallest number whose first battery is not a 0.
+    Smallest,
+    /// The largest sum of the digits spelled, so a 12 counts for 3; ties go
+    /// to the largest number.
+    MaxDigitSum,
+    /// The `n`-th largest distinct number, 1-based, so `Rank(1)` is
+    /// [`Objective::Largest`]. Selections spelling the same number, like
+    /// `1,12` and `11,2`, rank once, while `0,5` ranks as 5.
+    Rank(usize),
+}
+
+impl FromStr for Objective {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "largest" => Ok(Self::Largest),
+            "smallest" => Ok(Self::Smallest),
+            "digit-sum" => Ok(Self::MaxDigitSum),
+            _ => match s.strip_prefix("rank:").map(str::parse) {
+                Some(Ok(rank)) if rank > 0 => Ok(Self::Rank(rank)),
+                Some(_) => Err("rank must be a positive integer"),
+                None => Err("unknown objective"),
+            },
+        }
+    }
+}
+
+impl BatteryBank {
+    /// The best selection of exactly `k` batteries under `objective`, or
+    /// `None` when the bank holds fewer than `k` or no selection qualifies.
+    pub fn select_by(&self, k: usize, objective: Objective) -> Option<Selection> {
+        let values = self.joltages();
+        if k > values.len() {
+            return None;
+        }
+
+        let indices = match objective {
+            Objective::Largest => Self::max_subsequence_indices(&values, k),
+            Objective::Smallest if self.is_single_digit() => smallest_indices(&values, k)?,
+            Objective::Smallest => concat_indices(&values, k, |_| 0, Ordering::Less)?,
+            Objective::MaxDigitSum if self.is_single_digit() => max_sum_indices(&values, k),
+            Objective::MaxDigitSum => concat_indices(&values, k, digit_sum, Ordering::Greater)?,
+            Objective::Rank(rank) if self.is_single_digit() => ranked_indices(&values, k, rank)?,
+            Objective::Rank(rank) => ranked_concat_indices(&values, k, rank)?,
+        };
+        let chosen: Vec<u32> = indices.iter().map(|&i| values[i]).collect();
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+}
+
+/// Mirror image of the largest-number greedy: the smallest digit that still
+/// leaves room for the rest, earliest first, and never a leading 0.
+fn smallest_indices(values: &[u32], k: usize) -> Option<Vec<usize>> {
+    let n = values.len();
+    let mut indices = Vec::with_capacity(k);
+    let mut start = 0;
+
+    for remaining in (0..k).rev() {
+        let floor = u32::from(indices.is_empty());
+        let pick = (start..n - remaining)
+            .filter(|&i| values[i] >= floor)
+            .min_by_key(|&i| values[i])?;
+
+        indices.push(pick);
+        start = pick + 1;
+    }
+
+    Some(indices)
+}
+
+/// Every battery above the `k`-th largest joltage is needed for the best
+/// sum; the rest are filled with that joltage itself. Taking those as late
+/// as possible lets the larger ones lead, which makes the largest number.
+fn max_sum_indices(values: &[u32], k: usize) -> Vec<usize> {
+    if k == 0 {
+        return Vec::new();
+    }
+
+    let mut sorted = values.to_vec();
+    sorted.sort_unstable_by(|a, b| b.cmp(a));
+    let threshold = sorted[k - 1];
+
+    let mut spare = k - values.iter().filter(|&&v| v > threshold).count();
+    let mut indices = Vec::with_capacity(k);
+
+    for i in (0..values.len()).rev() {
+        if values[i] > threshold {
+            indices.push(i);
+        } else if values[i] == threshold && spare > 0 {
+            indices.push(i);
+            spare -= 1;
+        }
+    }
+
+    indices.reverse();
+    indices
+}
+
+/// Walks down the joltages from the largest, skipping whole groups of
+/// selections by how many distinct ones start with each joltage.
+fn ranked_indices(values: &[u32], k: usize, mut rank: usize) -> Option<Vec<usize>> {
+    if rank == 0 {
+        return None;
+    }
+
+    let n = values.len();
+    let mut distinct = values.to_vec();
+    distinct.sort_unstable();
+    distinct.dedup();
+    let m = distinct.len();
+
+    // next[i][d]: first position at or after `i` holding the `d`-th
+    // smallest joltage.
+    let mut next = vec![None; (n + 1) * m];
+    for i in (0..n).rev() {
+        next.copy_within((i + 1) * m..(i + 2) * m, i * m);
+        next[i * m + distinct.binary_search(&values[i]).unwrap()] = Some(i);
+    }
+    let next = |i: usize| &next[i * m..(i + 1) * m];
+
+    // count[i][len]: distinct selections of `len` batteries within
+    // `values[i..]`, saturated since ranks never get that large.
+    let width = k + 1;
+    let mut count = vec![0usize; (n + 1) * width];
+    for i in (0..=n).rev() {
+        count[i * width] = 1;
+
+        if i < n {
+            for len in 1..=k {
+                count[i * width + len] = next(i)
+                    .iter()
+                    .flatten()
+                    .map(|&j| count[(j + 1) * width + len - 1])
+                    .fold(0, usize::saturating_add);
+            }
+        }
+    }
+
+    let mut indices = Vec::with_capacity(k);
+    let mut start = 0;
+
+    for len in (1..=k).rev() {
+        let mut pick = None;
+
+        for j in next(start).iter().rev().flatten() {
+            let numbers = count[(j + 1) * width + len - 1];
+            if rank <= numbers {
+                pick = Some(*j);
+                break;
+            }
+
+            rank -= numbers;
+        }
+
+        let pick = pick?;
+        indices.push(pick);
+        start = pick + 1;
+    }
+
+    Some(indices)
+}
+
+fn digit_sum(mut joltage: u32) -> u64 {
+    let mut sum = 0;
+    while joltage > 0 {
+        sum += u64::from(joltage % 10);
+        joltage /= 10;
+    }
+
+    sum
+}
+
+/// [`ranked_indices`] for a bank that is not
+/// [single-digit](BatteryBank::is_single_digit), where different selections
+/// can spell the same number, like `1,12` and `11,2`.
+///
+/// Walks the numbers from the longest down, digit by digit and largest digit
+/// first, tracking every way the bank can be partway through spelling the
+/// digits so far. Ways that can no longer finish with exactly `k` batteries
+/// are dropped, so every branch ends in a number and the `rank`-th one is
+/// reached after O(rank · digits) steps.
+fn ranked_concat_indices(values: &[u32], k: usize, mut rank: usize) -> Option<Vec<usize>> {
+    if k == 0 {
+        return (rank == 1).then(Vec::new);
+    }
+
+    let search = Spellings::new(values, k);
+    let mut digits = Vec::new();
+
+    for len in (1..=search.max_len).rev() {
+        if search.walk(None, len, &mut digits, &mut rank) {
+            return Some(search.indices(&digits));
+        }
+    }
+
+    // only 0 is left, spelled by `k` zero batteries
+    let zeros: Vec<usize> = (0..values.len())
+        .filter(|&i| values[i] == 0)
+        .take(k)
+        .collect();
+    (rank == 1 && zeros.len() == k).then_some(zeros)
+}
+
+/// The numbers `k` batteries of a bank can spell, with leading 0 batteries
+/// dropped.
+struct Spellings<'a> {
+    values: &'a [u32],
+    digits: Vec<Vec<u8>>,
+    k: usize,
+    /// The longest number `k` batteries can spell.
+    max_len: usize,
+    /// Whether `m` batteries from `i..` spell `len` digits, at
+    /// [`Self::at`]`(i, m, len)`.
+    reach: Vec<bool>,
+    /// How many 0 batteries come before each position.
+    zeros: Vec<usize>,
+}
+
+/// The ways a bank can be partway through spelling some digits.
+struct Progress {
+    /// (battery, digits of it spelled, batteries used including it)
+    inside: Vec<(usize, usize, usize)>,
+    /// By count of batteries used, the earliest battery to finish the
+    /// digits as the last of them.
+    done: Vec<Option<usize>>,
+}
+
+impl<'a> Spellings<'a> {
+    fn new(values: &'a [u32], k: usize) -> Self {
+        let n = values.len();
+        let digits = spell(values);
+
+        let mut lens: Vec<usize> = digits.iter().map(Vec::len).collect();
+        lens.sort_unstable_by(|a, b| b.cmp(a));
+        let max_len = lens.iter().take(k).sum();
+
+        let mut search = Self {
+            values,
+            digits,
+            k,
+            max_len,
+            reach: vec![false; (n + 1) * (k + 1) * (max_len + 1)],
+            zeros: Vec::with_capacity(n + 1),
+        };
+
+        for i in (0..=n).rev() {
+            let at = search.at(i, 0, 0);
+            search.reach[at] = true;
+
+            if i < n {
+                let len = search.digits[i].len();
+                for m in 1..=k {
+                    for total in 0..=max_len {
+                        let at = search.at(i, m, total);
+                        search.reach[at] = search.reaches(i + 1, m, total)
+                            || total >= len && search.reaches(i + 1, m - 1, total - len);
+                    }
+                }
+            }
+        }
+
+        search.zeros.push(0);
+        for &value in values {
+            let before = search.zeros[search.zeros.len() - 1];
+            search.zeros.push(before + usize::from(value == 0));
+        }
+
+        search
+    }
+
+    fn at(&self, i: usize, m: usize, len: usize) -> usize {
+        (i * (self.k + 1) + m) * (self.max_len + 1) + len
+    }
+
+    fn reaches(&self, i: usize, m: usize, len: usize) -> bool {
+        len <= self.max_len && self.reach[self.at(i, m, len)]
+    }
+
+    /// Follows `progress`, or the very start when `None`, by one more
+    /// `digit`, keeping the ways that can still spell `left` more digits.
+    fn step(&self, progress: Option<&Progress>, digit: u8, left: usize) -> Option<Progress> {
+        let n = self.values.len();
+        let mut next = Progress {
+            inside: Vec::new(),
+            done: vec![None; self.k + 1],
+        };
+
+        let mut add = |t: usize, spelled: usize, used: usize| {
+            let rest = self.digits[t].len() - spelled;
+            if rest == 0 {
+                if self.reaches(t + 1, self.k - used, left) {
+                    next.done[used] = Some(next.done[used].map_or(t, |d: usize| d.min(t)));
+                }
+            } else if left >= rest && self.reaches(t + 1, self.k - used, left - rest) {
+                next.inside.push((t, spelled, used));
+            }
+        };
+
+        match progress {
+            None => {
+                for t in (0..n).filter(|&t| self.values[t] != 0 && self.digits[t][0] == digit) {
+                    for zeros in 0..=self.zeros[t].min(self.k - 1) {
+                        add(t, 1, zeros + 1);
+                    }
+                }
+            }
+            Some(progress) => {
+                for &(t, spelled, used) in &progress.inside {
+                    if self.digits[t][spelled] == digit {
+                        add(t, spelled + 1, used);
+                    }
+                }
+
+                for (used, last) in progress.done.iter().enumerate().take(self.k) {
+                    if let Some(last) = *last {
+                        for t in (last + 1..n).filter(|&t| self.digits[t][0] == digit) {
+                            add(t, 1, used + 1);
+                        }
+                    }
+                }
+            }
+        }
+
+        next.inside.sort_unstable();
+        next.inside.dedup();
+        (!next.inside.is_empty() || next.done.iter().any(Option::is_some)).then_some(next)
+    }
+
+    /// Counts `rank` down over the numbers of `left` more digits after
+    /// `spelled`, largest first, stopping with `spelled` on the one that
+    /// takes it to 0.
+    fn walk(
+        &self,
+        progress: Option<&Progress>,
+        left: usize,
+        spelled: &mut Vec<u8>,
+        rank: &mut usize,
+    ) -> bool {
+        if left == 0 {
+            *rank -= 1;
+            return *rank == 0;
+        }
+
+        for digit in (b'0'..=b'9').rev() {
+            if let Some(next) = self.step(progress, digit, left - 1) {
+                spelled.push(digit);
+                if self.walk(Some(&next), left - 1, spelled, rank) {
+                    return true;
+                }
+                spelled.pop();
+            }
+        }
+
+        false
+    }
+
+    /// The earliest batteries spelling `number`, which opens with a nonzero
+    /// digit.
+    fn indices(&self, number: &[u8]) -> Vec<usize> {
+        let n = self.values.len();
+        let (k, width) = (self.k, number.len() + 1);
+        let fits = |i: usize, at: usize| number[at..].starts_with(&self.digits[i]);
+
+        // spells[i][j][at]: whether `j` batteries from `i..` spell exactly
+        // `number[at..]`.
+        let mut spells = vec![false; (n + 1) * (k + 1) * width];
+        let cell = |i: usize, j: usize, at: usize| (i * (k + 1) + j) * width + at;
+        spells[cell(n, 0, number.len())] = true;
+        for i in (0..n).rev() {
+            let len = self.digits[i].len();
+            for j in 0..=k {
+                for at in 0..width {
+                    spells[cell(i, j, at)] = spells[cell(i + 1, j, at)]
+                        || j > 0 && fits(i, at) && spells[cell(i + 1, j - 1, at + len)];
+                }
+            }
+        }
+
+        let (first, zeros) = (0..n)
+            .filter(|&p| self.values[p] != 0 && fits(p, 0))
+            .find_map(|p| {
+                let len = self.digits[p].len();
+                (0..=self.zeros[p].min(k - 1))
+                    .find(|&z| spells[cell(p + 1, k - 1 - z, len)])
+                    .map(|z| (p, z))
+            })
+            .expect("the walk only stops on numbers the bank spells");
+
+        let mut indices: Vec<usize> = (0..first)
+            .filter(|&i| self.values[i] == 0)
+            .take(zeros)
+            .collect();
+        indices.push(first);
+
+        let mut at = self.digits[first].len();
+        for i in first + 1..n {
+            let left = k - indices.len();
+            if left > 0 && fits(i, at) && spells[cell(i + 1, left - 1, at + self.digits[i].len())] {
+                indices.push(i);
+                at += self.digits[i].len();
+            }
+        }
+
+        indices
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::INPUT_TEXT;
+    use crate::test_util::XorShift;
+
+    fn joltages(bank: &BatteryBank, selection: &Selection) -> Vec<u32> {
+        selection
+            .indices
+            .iter()
+            .map(|&i| bank.batteries[i].joltage)
+            .collect()
+    }
+
+    #[test]
+    fn test_objectives_example() {
+        let bank: BatteryBank = "818181911112111".parse().unwrap();
+        let select = |k, objective| bank.select_by(k, objective).unwrap();
+
+        assert_eq!(select(2, Objective::Smallest).indices, vec![1, 3]);
+        assert_eq!(select(3, Objective::MaxDigitSum).indices, vec![2, 4, 6]);
+        let chosen = joltages(&bank, &select(3, Objective::MaxDigitSum));
+        assert_eq!(chosen.iter().sum::<u32>(), 25);
+        assert_eq!(select(2, Objective::Rank(2)).indices, vec![6, 7]);
+        assert_eq!(
+            select(2, Objective::Rank(3)).joltage,
+            Joltage::from(89usize)
+        );
+
+        let zeros: BatteryBank = "0001".parse().unwrap();
+        assert_eq!(zeros.select_by(2, Objective::Smallest), None);
+        assert_eq!(zeros.select_by(2, Objective::Rank(3)), None);
+    }
+
+    #[test]
+    fn test_largest_objective_matches_select() {
+        for bank in BatteryBank::parse_all(INPUT_TEXT).unwrap() {
+            for k in [0, 1, 2, 12] {
+                assert_eq!(bank.select_by(k, Objective::Largest), bank.select(k));
+                assert_eq!(bank.select_by(k, Objective::Rank(1)), bank.select(k));
+            }
+        }
+    }
+
+    #[test]
+    fn test_multi_digit_objectives() {
+        let select = |bank: &str, k, objective| {
+            let bank: BatteryBank = bank.parse().unwrap();
+            bank.select_by(k, objective).unwrap().joltage
+        };
+
+        assert_eq!(
+            select("9,12,5", 2, Objective::Rank(1)),
+            Joltage::from(912usize)
+        );
+        assert_eq!(
+            select("12,3,45", 2, Objective::Smallest),
+            Joltage::from(123usize)
+        );
+        assert_eq!(
+            select("12,3,45", 2, Objective::Rank(2)),
+            Joltage::from(345usize)
+        );
+        assert_eq!(
+            select("9,12", 1, Objective::MaxDigitSum),
+            Joltage::from(9usize)
+        );
+        assert_eq!(
+            select("1,12,11,2", 2, Objective::Rank(3)),
+            Joltage::from(112usize)
+        );
+        assert_eq!(
+            select("0,5,10", 2, Objective::Rank(3)),
+            Joltage::from(5usize)
+        );
+    }
+
+    #[test]
+    fn test_objectives_against_brute_force() {
+        let mut rng = XorShift(0x853c_49e6_748f_ea9b);
+
+        for _ in 0..500 {
+            let n = 1 + rng.next(9);
+            let bank = rng.bank(n, &[0, 1, 1, 5, 9, 12, 11, 90]);
+            let k = 1 + rng.next(n);
+
+            let all: Vec<Vec<u32>> = (0u32..1 << n)
+                .filter(|mask| mask.count_ones() as usize == k)
+                .map(|mask| {
+                    (0..n)
+                        .filter(|i| mask & (1 << i) != 0)
+                        .map(|i| bank.batteries[i].joltage)
+                        .collect()
+                })
+                .collect();
+            let mut numbers: Vec<Joltage> = all.iter().map(|d| Joltage::concat(d)).collect();
+            numbers.sort_unstable_by(|a, b| b.cmp(a));
+            numbers.dedup();
+
+            let smallest = all
+                .iter()
+                .filter(|d| d[0] != 0)
+                .map(|d| Joltage::concat(d))
+                .min();
+            let selection = bank.select_by(k, Objective::Smallest);
+            assert_eq!(selection.map(|s| s.joltage), smallest, "{bank:?} k={k}");
+
+            let digit_sum = |d: &[u32]| d.iter().map(|&v| super::digit_sum(v)).sum::<u64>();
+            let best_sum = all
+                .iter()
+                .map(|d| (digit_sum(d), Joltage::concat(d)))
+                .max()
+                .unwrap();
+            let selection = bank.select_by(k, Objective::MaxDigitSum).unwrap();
+            let chosen = joltages(&bank, &selection);
+            assert_eq!(
+                (digit_sum(&chosen), selection.joltage),
+                best_sum,
+                "{bank:?} k={k}"
+            );
+
+            let ranks = (1..=numbers.len().min(30)).chain([numbers.len(), numbers.len() + 1]);
+            for rank in ranks {
+                let selection = bank.select_by(k, Objective::Rank(rank));
+
+                assert_eq!(
+                    selection.as_ref().map(|s| &s.joltage),
+                    numbers.get(rank - 1),
+                    "{bank:?} k={k} rank={rank}"
+                );
+                if let Some(selection) = selection {
+                    assert_eq!(
+                        Joltage::concat(&joltages(&bank, &selection)),
+                        selection.joltage
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_objective() {
+        assert_eq!("digit-sum".parse(), Ok(Objective::MaxDigitSum));
+        assert_eq!("rank:3".parse(), Ok(Objective::Rank(3)));
+        assert!("rank:0".parse::<Objective>().is_err());
+        assert!("median".parse::<Objective>().is_err());
+    }
+}
@@ -0,0 +1,152 @@
+use super::{BatteryBank, Joltage, Selection};
+
+/// Answers "best `k`-selection using only cells `l..=r`" for a fixed bank.
+///
+/// Building the index takes O(n log n); each query then costs O(k) instead
+/// of the O(r - l) a fresh `max_subsequence` over the slice would take.
+/// Banks with multi-digit joltages fall back to that fresh pass.
+#[derive(Debug, Clone)]
+pub struct RangeIndex {
+    joltages: Vec<u32>,
+    single_digit: bool,
+    /// `levels[j][i]`: position of the first maximum in `i..i + 2^j`.
+    levels: Vec<Vec<usize>>,
+}
+
+impl BatteryBank {
+    pub fn range_index(&self) -> RangeIndex {
+        let joltages = self.joltages();
+        let n = joltages.len();
+
+        let mut levels = vec![(0..n).collect::<Vec<_>>()];
+        let mut width = 1;
+        while width * 2 <= n {
+            let previous = levels.last().unwrap();
+            let level = (0..=n - width * 2)
+                .map(|i| first_max(&joltages, previous[i], previous[i + width]))
+                .collect();
+
+            levels.push(level);
+            width *= 2;
+        }
+
+        RangeIndex {
+            single_digit: self.is_single_digit(),
+            joltages,
+            levels,
+        }
+    }
+}
+
+impl RangeIndex {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.joltages.len()
+    }
+
+    /// Position of the first largest battery in `l..=r`.
+    pub fn max_position(&self, l: usize, r: usize) -> Option<usize> {
+        if l > r || r >= self.len() {
+            return None;
+        }
+
+        let level = (r - l + 1).ilog2() as usize;
+        let width = 1 << level;
+        let row = &self.levels[level];
+
+        Some(first_max(&self.joltages, row[l], row[r + 1 - width]))
+    }
+
+    /// The best selection of exactly `k` batteries within `l..=r`, with
+    /// indices into the whole bank.
+    pub fn select(&self, l: usize, r: usize, k: usize) -> Option<Selection> {
+        if l > r || r >= self.len() || k > r - l + 1 {
+            return None;
+        }
+
+        // the sparse table needs a single-digit bank, see
+        // `BatteryBank::is_single_digit`.
+        if !self.single_digit {
+            let indices = BatteryBank::max_subsequence_indices(&self.joltages[l..=r], k);
+            let chosen: Vec<u32> = indices.iter().map(|&i| self.joltages[l + i]).collect();
+
+            return Some(Selection {
+                indices: indices.iter().map(|i| i + l).collect(),
+                joltage: Joltage::concat(&chosen),
+            });
+        }
+
+        let mut indices = Vec::with_capacity(k);
+        let mut start = l;
+
+        // each pick must leave enough cells after it for the remaining ones.
+        for remaining in (0..k).rev() {
+            let pick = self.max_position(start, r - remaining)?;
+
+            indices.push(pick);
+            start = pick + 1;
+        }
+
+        let chosen: Vec<u32> = indices.iter().map(|&i| self.joltages[i]).collect();
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+
+    pub fn max_joltage(&self, l: usize, r: usize, k: usize) -> Option<Joltage> {
+        self.select(l, r, k).map(|selection| selection.joltage)
+    }
+}
+
+#[inline]
+fn first_max(joltages: &[u32], left: usize, right: usize) -> usize {
+    if joltages[right] > joltages[left] {
+        right
+    } else {
+        left
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::INPUT_TEXT;
+
+    #[test]
+    fn test_range_index_example() {
+        let bank: BatteryBank = "818181911112111".parse().unwrap();
+        let index = bank.range_index();
+
+        assert_eq!(index.max_position(0, 14), Some(6));
+        assert_eq!(index.max_position(7, 14), Some(11));
+        assert_eq!(index.max_joltage(1, 5, 2), Some(Joltage::from(88usize)));
+        assert_eq!(index.select(7, 10, 5), None);
+        assert_eq!(index.select(3, 15, 1), None);
+    }
+
+    #[test]
+    fn test_range_index_matches_slices() {
+        let bank = BatteryBank::parse_all(INPUT_TEXT).unwrap().swap_remove(0);
+        let index = bank.range_index();
+        let n = bank.batteries.len();
+
+        for l in (0..n).step_by(7) {
+            for r in (l..n).step_by(5) {
+                let slice = BatteryBank {
+                    batteries: bank.batteries[l..=r].to_vec(),
+                };
+
+                for k in [1, 2, 5, 12] {
+                    let expected = slice.select(k).map(|selection| Selection {
+                        indices: selection.indices.iter().map(|i| i + l).collect(),
+                        joltage: selection.joltage,
+                    });
+
+                    assert_eq!(index.select(l, r, k), expected, "{l}..={r} k={k}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read};
+
+use super::{Joltage, Selection};
+
+/// Best `k`-selection of a bank that arrives one battery at a time, holding
+/// on to at most `k + 1` batteries however long the bank is.
+///
+/// The best selection of a prefix plus one more battery is either the old
+/// selection or the old one minus a battery, plus the new one, and dropping
+/// the first battery smaller than its successor (or the last one, if none
+/// is) gives the best of those. `kept` is the non-increasing run in front
+/// of that drop point and `pending` the rest, so finding it is amortized
+/// O(1) per battery.
+#[derive(Debug, Clone)]
+pub struct StreamingSelector {
+    k: usize,
+    seen: usize,
+    kept: Vec<(usize, u32)>,
+    pending: VecDeque<(usize, u32)>,
+}
+
+impl StreamingSelector {
+    pub fn new(k: usize) -> Self {
+        Self {
+            k,
+            seen: 0,
+            kept: Vec::with_capacity(k + 1),
+            pending: VecDeque::with_capacity(k + 1),
+        }
+    }
+
+    /// Adds the next battery, which must be a single digit for the drop rule
+    /// to hold; see
+    /// [`BatteryBank::is_single_digit`](super::BatteryBank::is_single_digit).
+    pub fn push(&mut self, joltage: u32) {
+        debug_assert!(joltage <= 9, "streamed joltages are single digits");
+        self.pending.push_back((self.seen, joltage));
+        self.seen += 1;
+
+        if self.kept.len() + self.pending.len() <= self.k {
+            return;
+        }
+
+        loop {
+            match (self.kept.last(), self.pending.front()) {
+                (Some(&(_, top)), Some(&(_, next))) if top < next => {
+                    self.kept.pop();
+                    return;
+                }
+                (_, Some(_)) => {
+                    let next = self.pending.pop_front().unwrap();
+                    self.kept.push(next);
+                }
+                (_, None) => {
+                    self.kept.pop();
+                    return;
+                }
+            }
+        }
+    }
+
+    /// The same selection [`BatteryBank::select`](super::BatteryBank::select)
+    /// makes over everything pushed, or `None` when fewer than `k` were.
+    pub fn finish(self) -> Option<Selection> {
+        if self.seen < self.k {
+            return None;
+        }
+
+        let (indices, chosen): (Vec<usize>, Vec<u32>) =
+            self.kept.into_iter().chain(self.pending).unzip();
+
+        Some(Selection {
+            indices,
+            joltage: Joltage::concat(&chosen),
+        })
+    }
+}
+
+/// Reads a single bank of one digit per battery from `reader` and selects
+/// the best batteries for each of `sizes` in one pass. Whitespace is
+/// ignored so wrapped streams work; any other byte is an error.
+pub fn select_streaming<R: Read>(
+    mut reader: R,
+    sizes: &[usize],
+) -> io::Result<Vec<Option<Selection>>> {
+    let mut selectors: Vec<_> = sizes.iter().map(|&k| StreamingSelector::new(k)).collect();
+    let mut buffer = [0; 64 * 1024];
+
+    loop {
+        let read = match reader.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+
+        for &byte in &buffer[..read] {
+            match byte {
+                b'0'..=b'9' => {
+                    for selector in &mut selectors {
+                        selector.push((byte - b'0') as u32);
+                    }
+                }
+                _ if byte.is_ascii_whitespace() => {}
+                _ => {
+                    return Err(io::Error::new(
+                        ErrorKind::InvalidData,
+                        "unrecognized character in bank",
+                    ));
+                }
+            }
+        }
+    }
+
+    Ok(selectors
+        .into_iter()
+        .map(StreamingSelector::finish)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lobby::{BatteryBank, INPUT_TEXT};
+    use crate::test_util::XorShift;
+
+    #[test]
+    fn test_streaming_matches_select() {
+        let sizes = [0, 1, 2, 12, 50, 100, 101];
+
+        for line in INPUT_TEXT.lines() {
+            let bank: BatteryBank = line.parse().unwrap();
+            let streamed = select_streaming(line.as_bytes(), &sizes).unwrap();
+
+            for (&k, selection) in sizes.iter().zip(streamed) {
+                assert_eq!(selection, bank.select(k), "k = {k}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_streaming_random_banks() {
+        let mut rng = XorShift(0x1405_7b7e_f767_814f);
+
+        for _ in 0..500 {
+            let len = rng.next(40);
+            let bank = rng.bank(len, &[0, 1, 2, 2, 5, 9]);
+            let k = rng.next(20);
+
+            let mut selector = StreamingSelector::new(k);
+            for battery in &bank.batteries {
+                selector.push(battery.joltage);
+            }
+
+            assert_eq!(selector.finish(), bank.select(k), "{bank:?} k={k}");
+        }
+    }
+
+    #[test]
+    fn test_streaming_input_format() {
+        let streamed = select_streaming("8111\n11111\r\n11119\n".as_bytes(), &[2]).unwrap();
+        assert_eq!(streamed[0].as_ref().unwrap().indices, vec![0, 13]);
+
+        let error = select_streaming("81,9".as_bytes(), &[2]).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+    }
+}
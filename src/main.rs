@@ -3,12 +3,25 @@ use std::time::Instant;
 use crate::cafeteria::CafeteriaStats;
 
 mod cafeteria;
+mod cli;
 mod gift_shop;
 mod lobby;
 mod printing_department;
 mod secret_entrance;
+#[cfg(test)]
+mod test_util;
 
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        if let Err(err) = cli::run(&args) {
+            eprintln!("error: {err}");
+            std::process::exit(1);
+        }
+
+        return;
+    }
+
     println!("🎄 Advent of Code 2025 🎄");
     println!();
 
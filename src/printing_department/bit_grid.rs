@@ -0,0 +1,254 @@
+use std::str::FromStr;
+
+use super::{GridStats, RollGrid, floor_rows};
+
+/// A floor packed 64 cells to a word, for floors too large for a `bool` per
+/// cell.
+///
+/// Rows are padded to whole words with an empty ring around the floor, as
+/// in [`Grid`](super::Grid). Accessibility uses the default rule, fewer than
+/// 4 rolls among the 8 surrounding cells, and is worked out for a whole
+/// word at once by adding up the eight shifted neighbor masks bit by bit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitGrid {
+    /// Padded width in cells.
+    width: usize,
+    /// Padded height in cells.
+    height: usize,
+    words_per_row: usize,
+    words: Vec<u64>,
+}
+
+impl BitGrid {
+    #[inline]
+    fn word(&self, row: usize, i: usize) -> u64 {
+        if i < self.words_per_row {
+            self.words[row * self.words_per_row + i]
+        } else {
+            0
+        }
+    }
+
+    /// Word `i` of `row` as seen from the cell left of each bit, the cell
+    /// itself and the cell right of it: bit `x` of the three masks holds
+    /// cells `x - 1`, `x` and `x + 1`.
+    #[inline]
+    fn shifted(&self, row: usize, i: usize) -> [u64; 3] {
+        let word = self.word(row, i);
+        let before = if i > 0 { self.word(row, i - 1) } else { 0 };
+        let after = self.word(row, i + 1);
+
+        [
+            (word << 1) | (before >> 63),
+            word,
+            (word >> 1) | (after << 63),
+        ]
+    }
+
+    /// Rolls in word `i` of `row` with fewer than 4 rolls around them.
+    fn accessible(&self, row: usize, i: usize) -> u64 {
+        let [left, center, right] = self.shifted(row, i);
+        let [up_left, up, up_right] = self.shifted(row - 1, i);
+        let [down_left, down, down_right] = self.shifted(row + 1, i);
+
+        // a carry-save adder tree over the eight neighbor masks; a count of
+        // 4 or more always produces a carry into the fours place.
+        let (ones_a, twos_a) = full_add(up_left, up, up_right);
+        let (ones_b, twos_b) = full_add(left, right, down_left);
+        let (ones_c, twos_c) = full_add(down, down_right, 0);
+        let (_, twos_d) = full_add(ones_a, ones_b, ones_c);
+        let (twos, fours_a) = full_add(twos_a, twos_b, twos_c);
+        let fours_b = twos & twos_d;
+
+        center & !(fours_a | fours_b)
+    }
+
+    /// Runs the same cascade as [`run_simulation`](super::run_simulation),
+    /// a word at a time: whenever a word loses rolls, it and the words
+    /// around it are looked at again.
+    pub fn simulate(mut self) -> GridStats {
+        let (rows, words_per_row) = (self.height, self.words_per_row);
+        let mut queued = vec![false; self.words.len()];
+        let mut queue = Vec::new();
+
+        let mut first_wave = Vec::new();
+        for row in 1..rows.saturating_sub(1) {
+            for i in 0..words_per_row {
+                let removable = self.accessible(row, i);
+                if removable != 0 {
+                    first_wave.push((row, i, removable));
+                }
+            }
+        }
+
+        let mut removed = 0;
+        for &(row, i, removable) in &first_wave {
+            removed += removable.count_ones() as usize;
+            self.words[row * words_per_row + i] &= !removable;
+        }
+        let accessible = removed;
+
+        // the words whose rolls may have lost a neighbor when word `i` of
+        // `row` did, skipping the padding rows.
+        let around = |row: usize, i: usize| {
+            let columns = i.saturating_sub(1)..=(i + 1).min(words_per_row - 1);
+            (row - 1..=row + 1)
+                .filter(move |&r| r > 0 && r < rows - 1)
+                .flat_map(move |r| columns.clone().map(move |j| (r, j)))
+        };
+
+        let mut changed: Vec<(usize, usize)> =
+            first_wave.iter().map(|&(row, i, _)| (row, i)).collect();
+
+        loop {
+            for (row, i) in changed.drain(..).flat_map(|(row, i)| around(row, i)) {
+                if !queued[row * words_per_row + i] {
+                    queued[row * words_per_row + i] = true;
+                    queue.push((row, i));
+                }
+            }
+
+            let Some((row, i)) = queue.pop() else {
+                break;
+            };
+            queued[row * words_per_row + i] = false;
+
+            let removable = self.accessible(row, i);
+            if removable != 0 {
+                removed += removable.count_ones() as usize;
+                self.words[row * words_per_row + i] &= !removable;
+                changed.push((row, i));
+            }
+        }
+
+        GridStats {
+            accessible,
+            total_removable: removed,
+        }
+    }
+}
+
+/// Bitwise full adder: per bit, the sum and the carry of `a + b + c`.
+#[inline]
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let partial = a ^ b;
+
+    (partial ^ c, (a & b) | (partial & c))
+}
+
+impl RollGrid for BitGrid {
+    #[inline]
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    fn is_filled(&self, x: usize, y: usize) -> bool {
+        self.word(y, x / 64) >> (x % 64) & 1 == 1
+    }
+
+    #[inline]
+    fn clear(&mut self, x: usize, y: usize) {
+        self.words[y * self.words_per_row + x / 64] &= !(1 << (x % 64));
+    }
+}
+
+impl FromStr for BitGrid {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let rows = floor_rows(s)?;
+        let width = rows.first().map_or(0, |row| row.len()) + 2;
+        let height = rows.len() + 2;
+        let words_per_row = width.div_ceil(64);
+
+        let mut words = vec![0; words_per_row * height];
+        for (y, row) in rows.iter().enumerate() {
+            let start = (y + 1) * words_per_row;
+            for (x, _) in row.iter().enumerate().filter(|&(_, &cell)| cell == b'@') {
+                words[start + (x + 1) / 64] |= 1 << ((x + 1) % 64);
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            words_per_row,
+            words,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printing_department::{GRID, HeapGrid, RAW, run_simulation, run_waves};
+    use crate::test_util::XorShift;
+
+    #[test]
+    fn test_bit_grid_matches_grid() {
+        let grid: BitGrid = RAW.parse().unwrap();
+
+        assert_eq!(grid.clone().simulate(), run_simulation(GRID));
+        assert_eq!(run_simulation(grid), run_simulation(GRID));
+    }
+
+    #[test]
+    fn test_bit_grid_word_boundaries() {
+        let mut rng = XorShift(0x6a09_e667_f3bc_c908);
+
+        for width in [1, 2, 61, 62, 63, 64, 65, 127, 128, 130, 200] {
+            for density in [3, 5, 8] {
+                let height = 1 + rng.next(12);
+                let floor: Vec<String> = (0..height)
+                    .map(|_| {
+                        (0..width)
+                            .map(|_| if rng.next(10) < density { '@' } else { '.' })
+                            .collect()
+                    })
+                    .collect();
+                let floor = floor.join("\n");
+
+                let packed: BitGrid = floor.parse().unwrap();
+                let heap: HeapGrid = floor.parse().unwrap();
+
+                assert_eq!(
+                    packed.clone().simulate(),
+                    run_simulation(heap.clone()),
+                    "{width}x{height}"
+                );
+                assert_eq!(
+                    run_waves(packed).waves,
+                    run_waves(heap).waves,
+                    "{width}x{height}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_bit_grid_empty_floor() {
+        let grid: BitGrid = "".parse().unwrap();
+
+        assert_eq!(
+            grid.simulate(),
+            GridStats {
+                accessible: 0,
+                total_removable: 0
+            }
+        );
+    }
+
+    #[test]
+    fn test_full_add() {
+        let (sum, carry) = full_add(0b1111_0000, 0b1100_1100, 0b1010_1010);
+
+        assert_eq!(sum, 0b1001_0110);
+        assert_eq!(carry, 0b1110_1000);
+    }
+}
@@ -0,0 +1,41 @@
+use std::str::FromStr;
+
+/// What a neighbor lookup finds past the edge of the floor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Boundary {
+    /// Nothing; the floor is surrounded by empty space.
+    #[default]
+    Empty,
+    /// A wall, which counts as a filled neighbor.
+    Walls,
+    /// The opposite edge, as on circular racks: the floor wraps around in
+    /// both directions.
+    Torus,
+}
+
+/// Accepts `empty`, `walls` and `torus`.
+impl FromStr for Boundary {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "empty" => Ok(Self::Empty),
+            "walls" => Ok(Self::Walls),
+            "torus" => Ok(Self::Torus),
+            _ => Err("unknown boundary"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_boundary() {
+        assert_eq!("empty".parse(), Ok(Boundary::Empty));
+        assert_eq!("walls".parse(), Ok(Boundary::Walls));
+        assert_eq!("torus".parse(), Ok(Boundary::Torus));
+        assert!("wrap".parse::<Boundary>().is_err());
+    }
+}
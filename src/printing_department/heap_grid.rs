@@ -0,0 +1,402 @@
+use std::str::FromStr;
+
+use super::{AccessRule, Boundary, RollGrid, floor_rows};
+
+/// A floor sized from its input at runtime, stored row-major on the heap.
+///
+/// Like [`Grid`](super::Grid) it keeps a ring around the floor so neighbor
+/// lookups never need bounds checks. The ring is as thick as the access
+/// rule's neighborhood reaches once neighbors past the floor are brought in
+/// by [`Neighborhood::offsets_on`](super::Neighborhood::offsets_on), and
+/// `width` and `height` include it. It is empty unless
+/// [`Self::with_boundary`] says otherwise.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeapGrid {
+    width: usize,
+    height: usize,
+    padding: usize,
+    cells: Vec<bool>,
+    offsets: Vec<(isize, isize)>,
+    threshold: usize,
+    boundary: Boundary,
+}
+
+impl HeapGrid {
+    /// Parses a floor of `@` (roll) and `.` (empty) cells, every row as wide
+    /// as the first, whose rolls are accessible according to `rule`.
+    pub fn parse_with(input: &str, rule: &AccessRule) -> Result<Self, &'static str> {
+        let rows = floor_rows(input)?;
+        let (floor_width, floor_height) = (rows.first().map_or(0, |row| row.len()), rows.len());
+
+        let offsets = rule.neighborhood.offsets_on(floor_width, floor_height);
+        let padding = rule.neighborhood.radius_on(floor_width, floor_height);
+        let padded = |size: usize| padding.checked_mul(2)?.checked_add(size);
+        let (width, height) = padded(floor_width)
+            .zip(padded(floor_height))
+            .filter(|(width, height)| width.checked_mul(*height).is_some())
+            .ok_or("floor too large for the neighborhood")?;
+        let mut cells = vec![false; width * height];
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, &cell) in row.iter().enumerate() {
+                cells[(y + padding) * width + x + padding] = cell == b'@';
+            }
+        }
+
+        Ok(Self {
+            width,
+            height,
+            padding,
+            cells,
+            offsets,
+            threshold: rule.threshold,
+            boundary: Boundary::Empty,
+        })
+    }
+
+    /// Fills the ring the way `boundary` asks: left empty, walled up, or
+    /// with copies of the far side of the floor, which [`RollGrid::clear`]
+    /// keeps in step from then on.
+    pub fn with_boundary(mut self, boundary: Boundary) -> Self {
+        self.boundary = boundary;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.in_floor(x, y) {
+                    continue;
+                }
+
+                let index = self.index(x, y);
+                self.cells[index] = match boundary {
+                    Boundary::Empty => false,
+                    Boundary::Walls => true,
+                    Boundary::Torus => self
+                        .wrap(x, y)
+                        .is_some_and(|(x, y)| self.cells[self.index(x, y)]),
+                };
+            }
+        }
+
+        self
+    }
+
+    /// Width of the floor itself, without the padding ring.
+    #[inline]
+    pub fn floor_width(&self) -> usize {
+        self.width - 2 * self.padding
+    }
+
+    /// Height of the floor itself, without the padding ring.
+    #[inline]
+    pub fn floor_height(&self) -> usize {
+        self.height - 2 * self.padding
+    }
+
+    #[inline]
+    fn index(&self, x: usize, y: usize) -> usize {
+        y * self.width + x
+    }
+
+    #[inline]
+    fn in_floor(&self, x: usize, y: usize) -> bool {
+        let p = self.padding;
+
+        (p..self.width - p).contains(&x) && (p..self.height - p).contains(&y)
+    }
+
+    /// The floor cell that `(x, y)` lands on when the floor wraps around.
+    fn wrap(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let (floor_width, floor_height) = (self.floor_width(), self.floor_height());
+        if floor_width == 0 || floor_height == 0 {
+            return None;
+        }
+
+        let p = self.padding as isize;
+        let wrap = |v: usize, size: usize| (v as isize - p).rem_euclid(size as isize) as usize;
+
+        Some((
+            wrap(x, floor_width) + self.padding,
+            wrap(y, floor_height) + self.padding,
+        ))
+    }
+}
+
+impl RollGrid for HeapGrid {
+    #[inline]
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    fn is_filled(&self, x: usize, y: usize) -> bool {
+        self.cells[self.index(x, y)]
+    }
+
+    #[inline]
+    fn clear(&mut self, x: usize, y: usize) {
+        let index = self.index(x, y);
+        self.cells[index] = false;
+
+        if self.boundary == Boundary::Torus {
+            // every copy of the cell in the ring lies a whole number of
+            // floor widths and heights away from it.
+            let (floor_width, floor_height) = (self.floor_width(), self.floor_height());
+            for cy in (y % floor_height..self.height).step_by(floor_height) {
+                for cx in (x % floor_width..self.width).step_by(floor_width) {
+                    let index = self.index(cx, cy);
+                    self.cells[index] = false;
+                }
+            }
+        }
+    }
+
+    #[inline]
+    fn padding(&self) -> usize {
+        self.padding
+    }
+
+    #[inline]
+    fn offsets(&self) -> &[(isize, isize)] {
+        &self.offsets
+    }
+
+    #[inline]
+    fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    #[inline]
+    fn canonical(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        match self.boundary {
+            Boundary::Torus => self.wrap(x, y),
+            Boundary::Empty | Boundary::Walls => self.in_floor(x, y).then_some((x, y)),
+        }
+    }
+}
+
+impl FromStr for HeapGrid {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse_with(s, &AccessRule::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printing_department::{GRID, Neighborhood, RAW, run_simulation, run_waves};
+
+    const EXAMPLE_RAW: &str = include_str!("example-input.txt");
+
+    /// Removes accessible rolls one at a time, with plain bounds checks,
+    /// until none are left.
+    fn reference(input: &str, rule: &AccessRule, boundary: Boundary) -> (usize, usize) {
+        let mut rows: Vec<Vec<bool>> = input
+            .lines()
+            .map(|line| line.bytes().map(|b| b == b'@').collect())
+            .collect();
+        let (width, height) = (rows[0].len() as isize, rows.len() as isize);
+        let offsets = rule.neighborhood.offsets();
+
+        let accessible = |rows: &Vec<Vec<bool>>, x: usize, y: usize| {
+            let filled = offsets
+                .iter()
+                .filter(|&&(dx, dy)| {
+                    let (nx, ny) = (x as isize + dx, y as isize + dy);
+                    let outside = nx < 0 || ny < 0 || nx >= width || ny >= height;
+
+                    match boundary {
+                        _ if !outside => rows[ny as usize][nx as usize],
+                        Boundary::Empty => false,
+                        Boundary::Walls => true,
+                        Boundary::Torus => {
+                            rows[ny.rem_euclid(height) as usize][nx.rem_euclid(width) as usize]
+                        }
+                    }
+                })
+                .count();
+
+            rows[y][x] && filled < rule.threshold
+        };
+
+        let cells: Vec<(usize, usize)> = (0..rows.len())
+            .flat_map(|y| (0..rows[0].len()).map(move |x| (x, y)))
+            .collect();
+        let first = cells
+            .iter()
+            .filter(|&&(x, y)| accessible(&rows, x, y))
+            .count();
+
+        let mut total = 0;
+        while let Some(&(x, y)) = cells.iter().find(|&&(x, y)| accessible(&rows, x, y)) {
+            rows[y][x] = false;
+            total += 1;
+        }
+
+        (first, total)
+    }
+
+    #[test]
+    fn test_heap_grid_matches_const_grid() {
+        let grid: HeapGrid = RAW.parse().unwrap();
+
+        assert_eq!((grid.floor_width(), grid.floor_height()), (139, 139));
+        assert_eq!(run_simulation(grid), run_simulation(GRID));
+    }
+
+    #[test]
+    fn test_heap_grid_any_size() {
+        let stats = run_simulation("@@@@@\n@@@@@\r\n".parse::<HeapGrid>().unwrap());
+        assert_eq!(stats.accessible, 4);
+        assert_eq!(stats.total_removable, 10);
+
+        let empty: HeapGrid = "".parse().unwrap();
+        assert_eq!(run_simulation(empty).total_removable, 0);
+
+        assert_eq!("@@\n@".parse::<HeapGrid>(), Err("rows differ in width"));
+        assert_eq!("@x".parse::<HeapGrid>(), Err("unrecognized cell"));
+    }
+
+    #[test]
+    fn test_access_rules_against_reference() {
+        let rules = [
+            (Neighborhood::Moore, 4),
+            (Neighborhood::VonNeumann, 2),
+            (Neighborhood::VonNeumann, 3),
+            (Neighborhood::MooreRadius(2), 12),
+            (Neighborhood::MooreRadius(3), 20),
+            (Neighborhood::Offsets(vec![(0, -1), (1, -2), (-3, 0)]), 2),
+            (Neighborhood::Offsets(Vec::new()), 1),
+            (Neighborhood::Moore, 0),
+        ];
+
+        for (neighborhood, threshold) in rules {
+            let rule = AccessRule {
+                neighborhood,
+                threshold,
+            };
+
+            for boundary in [Boundary::Empty, Boundary::Walls, Boundary::Torus] {
+                let grid = HeapGrid::parse_with(EXAMPLE_RAW, &rule)
+                    .unwrap()
+                    .with_boundary(boundary);
+                let stats = run_simulation(grid);
+
+                assert_eq!(
+                    (stats.accessible, stats.total_removable),
+                    reference(EXAMPLE_RAW, &rule, boundary),
+                    "{rule:?} {boundary:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_neighbors_past_the_floor() {
+        for offsets in [vec![(100_000_000, 0)], vec![(isize::MIN, 0), (3, -25)]] {
+            let rule = AccessRule {
+                neighborhood: Neighborhood::Offsets(offsets),
+                threshold: 1,
+            };
+
+            for boundary in [Boundary::Empty, Boundary::Walls, Boundary::Torus] {
+                let grid = HeapGrid::parse_with(EXAMPLE_RAW, &rule)
+                    .unwrap()
+                    .with_boundary(boundary);
+                assert!(grid.padding() < 20, "{rule:?}");
+
+                let stats = run_simulation(grid);
+                assert_eq!(
+                    (stats.accessible, stats.total_removable),
+                    reference(EXAMPLE_RAW, &rule, boundary),
+                    "{rule:?} {boundary:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_padding_follows_radius() {
+        let rule = AccessRule {
+            neighborhood: Neighborhood::MooreRadius(3),
+            threshold: 4,
+        };
+        let grid = HeapGrid::parse_with("@@\n@.", &rule).unwrap();
+
+        assert_eq!(grid.padding(), 3);
+        assert_eq!((grid.width(), grid.height()), (8, 8));
+        assert_eq!((grid.floor_width(), grid.floor_height()), (2, 2));
+    }
+
+    #[test]
+    fn test_boundaries() {
+        let parse =
+            |input: &str, boundary| input.parse::<HeapGrid>().unwrap().with_boundary(boundary);
+
+        // a corridor between walls leaves every roll boxed in
+        let corridor = "@@@@@";
+        assert_eq!(
+            run_simulation(parse(corridor, Boundary::Empty)).total_removable,
+            5
+        );
+        assert_eq!(
+            run_simulation(parse(corridor, Boundary::Walls)).total_removable,
+            0
+        );
+
+        // a full ring of racks has no end to start from
+        assert_eq!(
+            run_simulation(parse(corridor, Boundary::Torus)).total_removable,
+            0
+        );
+
+        // turning a circular floor moves nothing about which rolls come off
+        let rows: Vec<&str> = EXAMPLE_RAW.lines().collect();
+        let turned: String = rows[3..]
+            .iter()
+            .chain(&rows[..3])
+            .map(|row| format!("{}{}\n", &row[4..], &row[..4]))
+            .collect();
+        let waves = |input: &str| {
+            let timeline = run_waves(parse(input, Boundary::Torus));
+            timeline.waves.iter().map(Vec::len).collect::<Vec<_>>()
+        };
+        assert_eq!(waves(&turned), waves(EXAMPLE_RAW));
+        assert_eq!(
+            run_waves(parse(EXAMPLE_RAW, Boundary::Torus)).total_removed(),
+            run_simulation(parse(EXAMPLE_RAW, Boundary::Torus)).total_removable
+        );
+    }
+
+    #[test]
+    fn test_torus_smaller_than_neighborhood() {
+        let rule = AccessRule {
+            neighborhood: Neighborhood::MooreRadius(3),
+            threshold: 30,
+        };
+
+        for input in ["@@\n@.", "@", "@.@\n.@@\n@@."] {
+            let grid = HeapGrid::parse_with(input, &rule)
+                .unwrap()
+                .with_boundary(Boundary::Torus);
+            let stats = run_simulation(grid);
+
+            assert_eq!(
+                (stats.accessible, stats.total_removable),
+                reference(input, &rule, Boundary::Torus),
+                "{input:?}"
+            );
+        }
+
+        let empty = HeapGrid::parse_with("", &rule)
+            .unwrap()
+            .with_boundary(Boundary::Torus);
+        assert_eq!(run_simulation(empty).total_removable, 0);
+    }
+}
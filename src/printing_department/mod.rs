@@ -1,4 +1,18 @@
-const RAW: &str = include_str!("input.txt");
+mod bit_grid;
+mod boundary;
+mod heap_grid;
+mod neighborhood;
+mod netpbm;
+mod timeline;
+
+pub use bit_grid::BitGrid;
+pub use boundary::Boundary;
+pub use heap_grid::HeapGrid;
+pub use neighborhood::{AccessRule, Neighborhood};
+pub use netpbm::Frame;
+pub use timeline::run_waves;
+
+pub const RAW: &str = include_str!("input.txt");
 const WIDTH: usize = calculate_width(RAW) + 2;
 const HEIGHT: usize = calculate_height(RAW) + 2;
 const GRID: Grid<HEIGHT, WIDTH> = Grid::parse(RAW);
@@ -19,6 +33,63 @@ struct Grid<const H: usize, const W: usize> {
     cells: [[bool; W]; H],
 }
 
+/// A padded floor that the removal cascade can run on. Coordinates include
+/// the ring of [`Self::padding`] cells around it, so the floor itself spans
+/// `padding..width - padding` by `padding..height - padding`.
+pub trait RollGrid {
+    fn width(&self) -> usize;
+    fn height(&self) -> usize;
+    fn is_filled(&self, x: usize, y: usize) -> bool;
+    fn clear(&mut self, x: usize, y: usize);
+
+    /// Thickness of the ring; at least the neighborhood's radius.
+    fn padding(&self) -> usize {
+        1
+    }
+
+    /// Neighbors of a cell, relative to it.
+    fn offsets(&self) -> &[(isize, isize)] {
+        &ADJACENT_OFFSETS
+    }
+
+    /// A roll is accessible with fewer than this many filled neighbors.
+    fn threshold(&self) -> usize {
+        4
+    }
+
+    /// The floor cell that padded coordinate `(x, y)` stands for, or `None`
+    /// if it is only part of the ring. Grids whose ring mirrors the far side
+    /// of the floor map it back here, so the cascade never removes a copy.
+    fn canonical(&self, x: usize, y: usize) -> Option<(usize, usize)> {
+        let p = self.padding();
+        let inside = (p..self.width() - p).contains(&x) && (p..self.height() - p).contains(&y);
+
+        inside.then_some((x, y))
+    }
+
+    fn is_accessible(&self, x: usize, y: usize) -> bool {
+        let threshold = self.threshold();
+        if threshold == 0 {
+            return false;
+        }
+
+        let mut filled_adjacent = 0;
+        for &(dx, dy) in self.offsets() {
+            let nx = (x as isize + dx) as usize;
+            let ny = (y as isize + dy) as usize;
+
+            if self.is_filled(nx, ny) {
+                filled_adjacent += 1;
+                if filled_adjacent >= threshold {
+                    return false;
+                }
+            }
+        }
+
+        true
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct GridStats {
     pub accessible: usize,
@@ -29,23 +100,15 @@ pub fn solve() -> GridStats {
     run_simulation(GRID)
 }
 
-fn run_simulation<const H: usize, const W: usize>(mut grid: Grid<H, W>) -> GridStats {
-    let mut queue = Vec::with_capacity((H * W) / 4);
-
-    let mut queued = [[false; W]; H];
-    // for y in 1..H - 1 {
-    //     for x in 1..W - 1 {
-    //         if grid.cells[y][x] && grid.is_accessible(x, y) {
-    //             grid.cells[y][x] = false;
-    //             queued[y][x] = true;
-    //             queue.push((x, y));
-    //         }
-    //     }
-    // }
-    for (y, row) in grid.cells.iter().enumerate().skip(1).take(H - 2) {
-        for (x, filled) in row.iter().enumerate().skip(1).take(W - 2) {
-            if *filled && grid.is_accessible(x, y) {
-                queued[y][x] = true;
+pub fn run_simulation<G: RollGrid>(mut grid: G) -> GridStats {
+    let (w, h, p) = (grid.width(), grid.height(), grid.padding());
+    let mut queue = Vec::with_capacity((h * w) / 4);
+
+    let mut queued = vec![false; h * w];
+    for y in p..h - p {
+        for x in p..w - p {
+            if grid.is_filled(x, y) && grid.is_accessible(x, y) {
+                queued[y * w + x] = true;
                 queue.push((x, y));
             }
         }
@@ -53,21 +116,28 @@ fn run_simulation<const H: usize, const W: usize>(mut grid: Grid<H, W>) -> GridS
 
     let accessible = queue.len();
     for &(x, y) in &queue {
-        grid.cells[y][x] = false;
+        grid.clear(x, y);
     }
 
+    // a removed roll matters to every cell that has it as a neighbor, which
+    // for lopsided neighborhoods is not the same as its own neighbors.
+    let dependents: Vec<_> = grid.offsets().iter().map(|&(dx, dy)| (-dx, -dy)).collect();
+
     let mut head = 0;
     while head < queue.len() {
         let (cx, cy) = queue[head];
         head += 1;
 
-        for &(dx, dy) in &ADJACENT_OFFSETS {
+        for &(dx, dy) in &dependents {
             let nx = (cx as isize + dx) as usize;
             let ny = (cy as isize + dy) as usize;
+            let Some((nx, ny)) = grid.canonical(nx, ny) else {
+                continue;
+            };
 
-            if grid.cells[ny][nx] && !queued[ny][nx] && grid.is_accessible(nx, ny) {
-                grid.cells[ny][nx] = false;
-                queued[ny][nx] = true;
+            if grid.is_filled(nx, ny) && !queued[ny * w + nx] && grid.is_accessible(nx, ny) {
+                grid.clear(nx, ny);
+                queued[ny * w + nx] = true;
                 queue.push((nx, ny));
             }
         }
@@ -101,26 +171,51 @@ impl<const H: usize, const W: usize> Grid<H, W> {
 
         Self { cells }
     }
+}
 
-    pub fn is_accessible(&self, x: usize, y: usize) -> bool {
-        let mut filled_adjacent = 0;
+impl<const H: usize, const W: usize> RollGrid for Grid<H, W> {
+    #[inline]
+    fn width(&self) -> usize {
+        W
+    }
 
-        for &(dx, dy) in &ADJACENT_OFFSETS {
-            let nx = (x as isize + dx) as usize;
-            let ny = (y as isize + dy) as usize;
+    #[inline]
+    fn height(&self) -> usize {
+        H
+    }
 
-            if self.cells[ny][nx] {
-                filled_adjacent += 1;
-                if filled_adjacent >= 4 {
-                    return false;
-                }
-            }
-        }
+    #[inline]
+    fn is_filled(&self, x: usize, y: usize) -> bool {
+        self.cells[y][x]
+    }
 
-        true
+    #[inline]
+    fn clear(&mut self, x: usize, y: usize) {
+        self.cells[y][x] = false;
     }
 }
 
+/// Rows of a floor drawn with `@` (roll) and `.` (empty) cells, one row per
+/// line and every row as wide as the first.
+fn floor_rows(input: &str) -> Result<Vec<&[u8]>, &'static str> {
+    let rows: Vec<&[u8]> = input
+        .lines()
+        .map(|line| line.trim_end_matches('\r').as_bytes())
+        .collect();
+    let width = rows.first().map_or(0, |row| row.len());
+
+    for row in &rows {
+        if row.len() != width {
+            return Err("rows differ in width");
+        }
+        if row.iter().any(|&cell| cell != b'@' && cell != b'.') {
+            return Err("unrecognized cell");
+        }
+    }
+
+    Ok(rows)
+}
+
 const fn calculate_width(s: &str) -> usize {
     let bytes = s.as_bytes();
     let mut i = 0;
@@ -0,0 +1,166 @@
+use std::str::FromStr;
+
+use super::ADJACENT_OFFSETS;
+
+/// Which cells around a roll count as its neighbors.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Neighborhood {
+    /// The eight surrounding cells.
+    #[default]
+    Moore,
+    /// The four orthogonally adjacent cells.
+    VonNeumann,
+    /// Every cell within `r` steps horizontally and vertically.
+    MooreRadius(usize),
+    /// Any set of `(dx, dy)` offsets, e.g. for forklifts that only reach
+    /// forward.
+    Offsets(Vec<(isize, isize)>),
+}
+
+/// When a forklift can get at a roll: fewer than `threshold` of its
+/// neighbors hold rolls themselves.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccessRule {
+    pub neighborhood: Neighborhood,
+    pub threshold: usize,
+}
+
+impl Default for AccessRule {
+    fn default() -> Self {
+        Self {
+            neighborhood: Neighborhood::Moore,
+            threshold: 4,
+        }
+    }
+}
+
+impl Neighborhood {
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Self::Moore => ADJACENT_OFFSETS.to_vec(),
+            Self::VonNeumann => vec![(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Self::MooreRadius(r) => {
+                let r = *r as isize;
+                (-r..=r)
+                    .flat_map(|dy| (-r..=r).map(move |dx| (dx, dy)))
+                    .filter(|&offset| offset != (0, 0))
+                    .collect()
+            }
+            Self::Offsets(offsets) => offsets.clone(),
+        }
+    }
+
+    /// [`Self::offsets`] for a `width` by `height` floor. A neighbor a whole
+    /// floor or more away along an axis is off the floor from every cell, so
+    /// only where it lands modulo the floor matters, for floors that wrap;
+    /// such offsets come back between one and two floors away.
+    pub fn offsets_on(&self, width: usize, height: usize) -> Vec<(isize, isize)> {
+        let fold = |d: isize, size: usize| {
+            let reach = d.unsigned_abs();
+            if reach <= size {
+                d
+            } else if size == 0 {
+                0
+            } else {
+                d.signum() * (size + reach % size) as isize
+            }
+        };
+
+        self.offsets()
+            .into_iter()
+            .map(|(dx, dy)| (fold(dx, width), fold(dy, height)))
+            .collect()
+    }
+
+    /// Furthest any neighbor lies from a cell of a `width` by `height` floor
+    /// along either axis, and so the padding a grid needs to look neighbors
+    /// up without bounds checks.
+    pub fn radius_on(&self, width: usize, height: usize) -> usize {
+        self.offsets_on(width, height)
+            .iter()
+            .map(|&(dx, dy)| dx.unsigned_abs().max(dy.unsigned_abs()))
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Accepts `moore`, `von-neumann`, `moore:<r>` and
+/// `offsets:<dx>,<dy>;<dx>,<dy>;...`.
+impl FromStr for Neighborhood {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(radius) = s.strip_prefix("moore:") {
+            return match radius.parse() {
+                Ok(radius) if radius > 0 => Ok(Self::MooreRadius(radius)),
+                _ => Err("radius must be a positive integer"),
+            };
+        }
+
+        if let Some(list) = s.strip_prefix("offsets:") {
+            let mut offsets = Vec::new();
+
+            for pair in list.split(';').map(str::trim) {
+                let (dx, dy) = pair.split_once(',').ok_or("offset must be `dx,dy`")?;
+                let parse = |d: &str| d.trim().parse::<isize>().map_err(|_| "invalid offset");
+                let offset = (parse(dx)?, parse(dy)?);
+
+                if offset == (0, 0) {
+                    return Err("offset 0,0 is the cell itself");
+                }
+                if !offsets.contains(&offset) {
+                    offsets.push(offset);
+                }
+            }
+
+            return Ok(Self::Offsets(offsets));
+        }
+
+        match s {
+            "moore" => Ok(Self::Moore),
+            "von-neumann" => Ok(Self::VonNeumann),
+            _ => Err("unknown neighborhood"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_neighborhood_offsets() {
+        assert_eq!(Neighborhood::MooreRadius(1).offsets(), ADJACENT_OFFSETS);
+        assert_eq!(Neighborhood::MooreRadius(2).offsets().len(), 24);
+        assert_eq!(Neighborhood::VonNeumann.radius_on(10, 10), 1);
+        assert_eq!(Neighborhood::MooreRadius(3).radius_on(10, 10), 3);
+        assert_eq!(
+            Neighborhood::Offsets(vec![(0, 2), (-1, 0)]).radius_on(10, 10),
+            2
+        );
+        assert_eq!(Neighborhood::Offsets(Vec::new()).radius_on(10, 10), 0);
+    }
+
+    #[test]
+    fn test_offsets_past_the_floor() {
+        let far = Neighborhood::Offsets(vec![(100_000_000, 3), (isize::MIN, -12), (-7, 4)]);
+
+        assert_eq!(far.offsets_on(7, 5), vec![(9, 3), (-8, -7), (-7, 4)]);
+        assert_eq!(far.radius_on(7, 5), 9);
+        assert_eq!(far.offsets_on(0, 0), vec![(0, 0); 3]);
+    }
+
+    #[test]
+    fn test_parse_neighborhood() {
+        assert_eq!("von-neumann".parse(), Ok(Neighborhood::VonNeumann));
+        assert_eq!("moore:2".parse(), Ok(Neighborhood::MooreRadius(2)));
+        assert_eq!(
+            "offsets: 0,-1; 0,-2;0,-1".parse(),
+            Ok(Neighborhood::Offsets(vec![(0, -1), (0, -2)]))
+        );
+        assert!("moore:0".parse::<Neighborhood>().is_err());
+        assert!("offsets:0,0".parse::<Neighborhood>().is_err());
+        assert!("offsets:1".parse::<Neighborhood>().is_err());
+        assert!("hex".parse::<Neighborhood>().is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use std::io::{self, Write};
+
+use super::timeline::RemovalTimeline;
+
+/// A moment of the removal timeline worth drawing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame {
+    /// The floor as parsed, before anything is removed.
+    Initial,
+    /// The floor as wave `n` (1-based) is taken away; its rolls are
+    /// highlighted and earlier waves are already gone.
+    Wave(usize),
+    /// The floor after the last wave, with only the rolls that stay.
+    Final,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cell {
+    Empty,
+    /// A roll used to be here.
+    Cleared,
+    /// The roll goes in the wave being drawn.
+    Removing,
+    /// Roll that goes in the first wave.
+    FirstWave,
+    /// Roll that goes in a later wave.
+    LaterWave,
+    /// Roll that is never removed.
+    Stays,
+}
+
+impl Cell {
+    fn rgb(self) -> [u8; 3] {
+        match self {
+            Cell::Empty => [255, 255, 255],
+            Cell::Cleared => [225, 225, 225],
+            Cell::Removing => [255, 190, 0],
+            Cell::FirstWave => [46, 160, 67],
+            Cell::LaterWave => [31, 111, 235],
+            Cell::Stays => [200, 30, 45],
+        }
+    }
+
+    fn gray(self) -> u8 {
+        match self {
+            Cell::Empty => 255,
+            Cell::Cleared => 230,
+            Cell::Removing => 180,
+            Cell::FirstWave => 130,
+            Cell::LaterWave => 80,
+            Cell::Stays => 0,
+        }
+    }
+}
+
+impl RemovalTimeline {
+    fn cell(&self, x: usize, y: usize, frame: Frame) -> Cell {
+        if !self.has_roll(x, y) {
+            return Cell::Empty;
+        }
+
+        let round = self.round_of(x, y);
+        let drawn = match frame {
+            Frame::Initial => 0,
+            Frame::Wave(n) => n,
+            Frame::Final => usize::MAX,
+        };
+
+        match round {
+            Some(round) if round < drawn => Cell::Cleared,
+            Some(round) if round == drawn => Cell::Removing,
+            Some(1) => Cell::FirstWave,
+            Some(_) => Cell::LaterWave,
+            None => Cell::Stays,
+        }
+    }
+
+    /// Every frame of the removal in order: initial, each wave, final.
+    pub fn frames(&self) -> impl Iterator<Item = Frame> {
+        std::iter::once(Frame::Initial)
+            .chain((1..=self.waves.len()).map(Frame::Wave))
+            .chain(std::iter::once(Frame::Final))
+    }
+
+    /// Writes `frame` as a binary PPM image, each cell `scale` pixels wide.
+    pub fn write_ppm<W: Write>(&self, out: &mut W, frame: Frame, scale: usize) -> io::Result<()> {
+        self.write_image(out, frame, scale, b"P6", |cell, row| row.extend(cell.rgb()))
+    }
+
+    /// Writes `frame` as a binary PGM image, each cell `scale` pixels wide.
+    pub fn write_pgm<W: Write>(&self, out: &mut W, frame: Frame, scale: usize) -> io::Result<()> {
+        self.write_image(out, frame, scale, b"P5", |cell, row| row.push(cell.gray()))
+    }
+
+    /// Writes every frame as consecutive PPM images into one stream, which
+    /// tools like `ffmpeg -f ppm_pipe` play back as an animation. Returns
+    /// the number of frames written.
+    pub fn write_animation<W: Write>(&self, out: &mut W, scale: usize) -> io::Result<usize> {
+        let mut frames = 0;
+        for frame in self.frames() {
+            self.write_ppm(out, frame, scale)?;
+            frames += 1;
+        }
+
+        Ok(frames)
+    }
+
+    fn write_image<W: Write>(
+        &self,
+        out: &mut W,
+        frame: Frame,
+        scale: usize,
+        magic: &[u8],
+        push: impl Fn(Cell, &mut Vec<u8>),
+    ) -> io::Result<()> {
+        let scale = scale.max(1);
+
+        out.write_all(magic)?;
+        write!(
+            out,
+            "\n{} {}\n255\n",
+            self.width * scale,
+            self.height * scale
+        )?;
+
+        let mut row = Vec::new();
+        for y in 0..self.height {
+            row.clear();
+            for x in 0..self.width {
+                let cell = self.cell(x, y, frame);
+                for _ in 0..scale {
+                    push(cell, &mut row);
+                }
+            }
+
+            for _ in 0..scale {
+                out.write_all(&row)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printing_department::{HeapGrid, run_waves};
+
+    const EXAMPLE_RAW: &str = include_str!("example-input.txt");
+
+    fn example() -> RemovalTimeline {
+        run_waves(EXAMPLE_RAW.parse::<HeapGrid>().unwrap())
+    }
+
+    /// Header and pixel data of a binary netpbm image.
+    fn split(image: &[u8]) -> (String, &[u8]) {
+        let mut newlines = image.iter().enumerate().filter(|&(_, &b)| b == b'\n');
+        let end = newlines.nth(2).unwrap().0 + 1;
+
+        (
+            String::from_utf8(image[..end].to_vec()).unwrap(),
+            &image[end..],
+        )
+    }
+
+    #[test]
+    fn test_ppm_frames() {
+        let timeline = example();
+        let pixel = |image: &[u8], x: usize, y: usize| {
+            let (_, data) = split(image);
+            let at = (y * 10 + x) * 3;
+            [data[at], data[at + 1], data[at + 2]]
+        };
+
+        let mut initial = Vec::new();
+        timeline.write_ppm(&mut initial, Frame::Initial, 1).unwrap();
+        assert_eq!(split(&initial).0, "P6\n10 10\n255\n");
+        assert_eq!(split(&initial).1.len(), 300);
+        assert_eq!(pixel(&initial, 0, 0), Cell::Empty.rgb());
+        assert_eq!(pixel(&initial, 2, 0), Cell::FirstWave.rgb());
+        assert_eq!(pixel(&initial, 7, 0), Cell::LaterWave.rgb());
+        assert_eq!(pixel(&initial, 1, 0), Cell::Empty.rgb());
+
+        let mut second = Vec::new();
+        timeline.write_ppm(&mut second, Frame::Wave(2), 1).unwrap();
+        assert_eq!(pixel(&second, 2, 0), Cell::Cleared.rgb());
+        assert_eq!(pixel(&second, 7, 0), Cell::Removing.rgb());
+
+        let mut last = Vec::new();
+        timeline.write_ppm(&mut last, Frame::Final, 1).unwrap();
+        let stays = split(&last)
+            .1
+            .chunks(3)
+            .filter(|&rgb| rgb == Cell::Stays.rgb())
+            .count();
+        assert_eq!(stays, 28);
+    }
+
+    #[test]
+    fn test_pgm_scale() {
+        let mut image = Vec::new();
+        example().write_pgm(&mut image, Frame::Initial, 3).unwrap();
+
+        let (header, data) = split(&image);
+        assert_eq!(header, "P5\n30 30\n255\n");
+        assert_eq!(data.len(), 900);
+        // (2, 0) goes in the first wave and covers pixels 6..9 of rows 0..3
+        for y in 0..3 {
+            assert_eq!(data[y * 30 + 5..y * 30 + 10], [255, 130, 130, 130, 130]);
+        }
+    }
+
+    #[test]
+    fn test_animation_frames() {
+        let timeline = example();
+        let mut stream = Vec::new();
+
+        let frames = timeline.write_animation(&mut stream, 2).unwrap();
+        assert_eq!(frames, 11);
+        assert_eq!(stream.len(), 11 * ("P6\n20 20\n255\n".len() + 20 * 20 * 3));
+    }
+}
@@ -0,0 +1,170 @@
+use super::RollGrid;
+
+/// The removal cascade played out in synchronous rounds: every roll that is
+/// accessible at the start of a round goes at once, and only then are the
+/// rolls around them looked at again.
+///
+/// Coordinates are on the floor itself, without the padding ring, with
+/// `(0, 0)` in the top left corner.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalTimeline {
+    pub width: usize,
+    pub height: usize,
+    /// Rolls removed in each round, in row-major order; round 1 first.
+    pub waves: Vec<Vec<(usize, usize)>>,
+    /// Round in which each cell's roll goes, row-major; `None` for empty
+    /// cells and rolls that are never removed.
+    rounds: Vec<Option<usize>>,
+    /// Whether each cell starts out holding a roll, row-major.
+    rolls: Vec<bool>,
+}
+
+impl RemovalTimeline {
+    /// 1-based round in which the roll at `(x, y)` is removed.
+    #[inline]
+    pub fn round_of(&self, x: usize, y: usize) -> Option<usize> {
+        self.rounds[y * self.width + x]
+    }
+
+    #[inline]
+    pub fn has_roll(&self, x: usize, y: usize) -> bool {
+        self.rolls[y * self.width + x]
+    }
+
+    pub fn total_removed(&self) -> usize {
+        self.waves.iter().map(Vec::len).sum()
+    }
+
+    /// Every roll with its removal round, row-major.
+    pub fn rolls(&self) -> impl Iterator<Item = ((usize, usize), Option<usize>)> + '_ {
+        (0..self.height)
+            .flat_map(move |y| (0..self.width).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.has_roll(x, y))
+            .map(|(x, y)| ((x, y), self.round_of(x, y)))
+    }
+}
+
+pub fn run_waves<G: RollGrid>(mut grid: G) -> RemovalTimeline {
+    let (w, h, p) = (grid.width(), grid.height(), grid.padding());
+    let (width, height) = (w - 2 * p, h - 2 * p);
+
+    let mut rounds = vec![None; width * height];
+    let mut rolls = vec![false; width * height];
+    let mut wave = Vec::new();
+
+    for y in p..h - p {
+        for x in p..w - p {
+            if grid.is_filled(x, y) {
+                rolls[(y - p) * width + x - p] = true;
+                if grid.is_accessible(x, y) {
+                    wave.push((x, y));
+                }
+            }
+        }
+    }
+
+    let dependents: Vec<_> = grid.offsets().iter().map(|&(dx, dy)| (-dx, -dy)).collect();
+    let mut candidate = vec![false; w * h];
+    let mut waves = Vec::new();
+
+    while !wave.is_empty() {
+        for &(x, y) in &wave {
+            grid.clear(x, y);
+            rounds[(y - p) * width + x - p] = Some(waves.len() + 1);
+        }
+
+        // only rolls next to this round's removals can have become
+        // accessible; check them once everything of this round is gone.
+        let mut next = Vec::new();
+        for &(x, y) in &wave {
+            for &(dx, dy) in &dependents {
+                let nx = (x as isize + dx) as usize;
+                let ny = (y as isize + dy) as usize;
+                let Some((nx, ny)) = grid.canonical(nx, ny) else {
+                    continue;
+                };
+
+                if grid.is_filled(nx, ny) && !candidate[ny * w + nx] {
+                    candidate[ny * w + nx] = true;
+                    next.push((nx, ny));
+                }
+            }
+        }
+
+        for &(x, y) in &next {
+            candidate[y * w + x] = false;
+        }
+        next.retain(|&(x, y)| grid.is_accessible(x, y));
+        next.sort_unstable_by_key(|&(x, y)| (y, x));
+
+        waves.push(wave.iter().map(|&(x, y)| (x - p, y - p)).collect());
+        wave = next;
+    }
+
+    RemovalTimeline {
+        width,
+        height,
+        waves,
+        rounds,
+        rolls,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::printing_department::{AccessRule, GRID, HeapGrid, Neighborhood, run_simulation};
+
+    const EXAMPLE_RAW: &str = include_str!("example-input.txt");
+
+    #[test]
+    fn test_waves_example() {
+        let timeline = run_waves(EXAMPLE_RAW.parse::<HeapGrid>().unwrap());
+
+        let sizes: Vec<usize> = timeline.waves.iter().map(Vec::len).collect();
+
+        assert_eq!(sizes, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+        assert_eq!(timeline.waves[0][..3], [(2, 0), (3, 0), (5, 0)]);
+        assert_eq!(timeline.round_of(2, 0), Some(1));
+        assert_eq!(timeline.round_of(0, 0), None);
+        assert!(!timeline.has_roll(0, 0));
+        assert_eq!(
+            timeline
+                .rolls()
+                .filter(|(_, round)| round.is_none())
+                .count(),
+            28
+        );
+    }
+
+    #[test]
+    fn test_waves_match_cascade() {
+        let stats = run_simulation(GRID);
+        let timeline = run_waves(GRID);
+
+        assert_eq!(timeline.waves[0].len(), stats.accessible);
+        assert_eq!(timeline.total_removed(), stats.total_removable);
+
+        let rule = AccessRule {
+            neighborhood: Neighborhood::Offsets(vec![(1, 0), (0, 1), (2, 2)]),
+            threshold: 2,
+        };
+        let grid = HeapGrid::parse_with(EXAMPLE_RAW, &rule).unwrap();
+        assert_eq!(
+            run_waves(grid.clone()).total_removed(),
+            run_simulation(grid).total_removable
+        );
+    }
+
+    #[test]
+    fn test_wave_rounds_are_consistent() {
+        let timeline = run_waves(GRID);
+
+        for (round, wave) in timeline.waves.iter().enumerate() {
+            assert!(wave.windows(2).all(|w| (w[0].1, w[0].0) < (w[1].1, w[1].0)));
+            for &(x, y) in wave {
+                assert_eq!(timeline.round_of(x, y), Some(round + 1));
+            }
+        }
+    }
+}
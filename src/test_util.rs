@@ -0,0 +1,31 @@
+use crate::lobby::{Battery, BatteryBank};
+
+/// A small, seeded xorshift generator, so the randomized tests that check
+/// fast paths against brute force see the same cases on every run.
+pub struct XorShift(pub u64);
+
+impl XorShift {
+    /// A value in `0..bound`.
+    pub fn next(&mut self, bound: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+
+        (self.0 % bound as u64) as usize
+    }
+
+    pub fn pick<T: Copy>(&mut self, choices: &[T]) -> T {
+        choices[self.next(choices.len())]
+    }
+
+    /// A bank of `len` batteries with joltages picked from `joltages`.
+    pub fn bank(&mut self, len: usize, joltages: &[u32]) -> BatteryBank {
+        BatteryBank {
+            batteries: (0..len)
+                .map(|_| Battery {
+                    joltage: self.pick(joltages),
+                })
+                .collect(),
+        }
+    }
+}